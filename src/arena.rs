@@ -0,0 +1,375 @@
+use crate::{
+    Action, GameConfig, GameError, ReflectMode, Wizard, BLOOD_MAGIC_HEALTH_COST, EMPOWER_DAMAGE,
+    INFUSE_HEALTH, MANA_BURN_AMOUNT, MAX_CHARGES, REFLECT_DAMAGE,
+};
+use std::fmt;
+
+/*
+Free-for-all between any number of wizards.
+Every wizard picks an action and a target each turn and everything resolves
+simultaneously against the state at the start of the turn.
+
+Reflect bounces an attack back at whoever cast it, exactly once.
+The bounced attack is never checked against the attacker's own action, so
+reflect chains can't form: A attacking B's Reflect always lands on A, even if
+C is attacking A at the same time.
+*/
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ArenaMove {
+    pub action: Action,
+    // Index of the wizard the action is aimed at, ignored by non-attacks.
+    pub target: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArenaError {
+    // Every wizard, dead or alive, must submit exactly one move.
+    WrongMoveCount {
+        expected: usize,
+        got: usize,
+    },
+    InvalidTarget {
+        wizard: usize,
+        target: usize,
+    },
+    NotEnoughMana {
+        wizard: usize,
+        action: Action,
+        have: u8,
+        need: u8,
+    },
+    // BloodMagic would have killed the caster.
+    NotEnoughHealth {
+        wizard: usize,
+        have: u8,
+    },
+    // Teleport was cast last turn.
+    OnCooldown {
+        wizard: usize,
+        action: Action,
+    },
+    // Exchange, Surrender, Foresight and Compel only make sense between two wizards,
+    // and there are no combos.
+    Unsupported {
+        wizard: usize,
+        action: Action,
+    },
+}
+
+impl fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArenaError::WrongMoveCount { expected, got } => {
+                write!(f, "expected {} moves, got {}", expected, got)
+            }
+            ArenaError::InvalidTarget { wizard, target } => {
+                write!(f, "wizard {} can't target wizard {}", wizard, target)
+            }
+            ArenaError::NotEnoughMana {
+                wizard,
+                action,
+                have,
+                need,
+            } => write!(
+                f,
+                "wizard {} did not have enough mana for {:?} (have {}, need {})",
+                wizard, action, have, need
+            ),
+            ArenaError::NotEnoughHealth { wizard, have } => write!(
+                f,
+                "wizard {} did not have enough health for BloodMagic (have {})",
+                wizard, have
+            ),
+            ArenaError::OnCooldown { wizard, action } => {
+                write!(
+                    f,
+                    "wizard {} can't cast {:?} twice in a row",
+                    wizard, action
+                )
+            }
+            ArenaError::Unsupported { wizard, action } => {
+                write!(
+                    f,
+                    "wizard {} cast {:?}, which the arena doesn't support",
+                    wizard, action
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArenaError {}
+
+fn is_targeted(action: Action) -> bool {
+    action.is_offensive()
+}
+
+pub struct Arena {
+    wizards: Vec<Wizard>,
+    // Team of every wizard. Wizards on the same team never win against each other.
+    teams: Vec<usize>,
+    turn_count: u32,
+    config: GameConfig,
+}
+
+impl Arena {
+    pub fn new(wizards: usize) -> Arena {
+        Arena::with_config(wizards, GameConfig::default()).expect("default config is valid")
+    }
+
+    // Free-for-all, every wizard on their own team.
+    pub fn with_config(wizards: usize, config: GameConfig) -> Result<Arena, GameError> {
+        Arena::with_teams((0..wizards).collect(), config)
+    }
+
+    // One wizard per entry of `teams`, entries with the same value playing together.
+    // The GameConfig fields marked duel only have no effect here.
+    pub fn with_teams(teams: Vec<usize>, config: GameConfig) -> Result<Arena, GameError> {
+        config.validate()?;
+        Ok(Arena {
+            wizards: teams.iter().map(|_| Wizard::new(&config)).collect(),
+            teams,
+            turn_count: 0,
+            config,
+        })
+    }
+
+    pub fn team(&self, index: usize) -> usize {
+        self.teams[index]
+    }
+
+    // Mana of every living wizard on `team` put together.
+    pub fn team_mana(&self, team: usize) -> u32 {
+        self.teammates(team)
+            .map(|index| self.wizards[index].mana as u32)
+            .sum()
+    }
+
+    fn teammates(&self, team: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.wizards.len())
+            .filter(move |&index| self.teams[index] == team && self.wizards[index].health > 0)
+    }
+
+    pub fn wizard(&self, index: usize) -> &Wizard {
+        &self.wizards[index]
+    }
+
+    pub fn turn_count(&self) -> u32 {
+        self.turn_count
+    }
+
+    // Indices of every wizard still above 0 HP.
+    pub fn survivors(&self) -> Vec<usize> {
+        (0..self.wizards.len())
+            .filter(|&index| self.wizards[index].health > 0)
+            .collect()
+    }
+
+    // Teams with at least one wizard still standing.
+    pub fn surviving_teams(&self) -> Vec<usize> {
+        let mut teams: Vec<usize> = self
+            .survivors()
+            .into_iter()
+            .map(|index| self.teams[index])
+            .collect();
+        teams.sort();
+        teams.dedup();
+        teams
+    }
+
+    // The arena is over once at most one team is left standing.
+    pub fn is_over(&self) -> bool {
+        self.surviving_teams().len() <= 1
+    }
+
+    fn check_move(&self, index: usize, mv: ArenaMove) -> Result<(), ArenaError> {
+        if matches!(
+            mv.action,
+            Action::Exchange
+                | Action::Surrender
+                | Action::Combo
+                | Action::Foresight
+                | Action::Compel
+        ) {
+            return Err(ArenaError::Unsupported {
+                wizard: index,
+                action: mv.action,
+            });
+        }
+        if is_targeted(mv.action) && (mv.target == index || mv.target >= self.wizards.len()) {
+            return Err(ArenaError::InvalidTarget {
+                wizard: index,
+                target: mv.target,
+            });
+        }
+        let last_action = self.wizards[index].last_action;
+        if mv.action == Action::Teleport && last_action == Some(Action::Teleport) {
+            return Err(ArenaError::OnCooldown {
+                wizard: index,
+                action: mv.action,
+            });
+        }
+        let have = self.wizards[index].mana;
+        let need = self.config.spells.mana_spent(mv.action);
+        // With shared mana the whole team's spending is checked at once in `check_team_mana`.
+        if !self.config.shared_team_mana && have < need {
+            return Err(ArenaError::NotEnoughMana {
+                wizard: index,
+                action: mv.action,
+                have,
+                need,
+            });
+        }
+        let health = self.wizards[index].health;
+        if mv.action == Action::BloodMagic && health <= BLOOD_MAGIC_HEALTH_COST {
+            return Err(ArenaError::NotEnoughHealth {
+                wizard: index,
+                have: health,
+            });
+        }
+        Ok(())
+    }
+
+    // Every team must be able to pay for all of its members' spells out of its pool.
+    fn check_team_mana(&self, moves: &[ArenaMove]) -> Result<(), ArenaError> {
+        for team in self.surviving_teams() {
+            let mut pool = self.team_mana(team);
+            for index in self.teammates(team) {
+                let action = moves[index].action;
+                let need = self.config.spells.mana_spent(action) as u32;
+                if pool < need {
+                    return Err(ArenaError::NotEnoughMana {
+                        wizard: index,
+                        action,
+                        have: pool.min(u8::MAX as u32) as u8,
+                        need: need as u8,
+                    });
+                }
+                pool -= need;
+            }
+        }
+        Ok(())
+    }
+
+    // Takes the cost from the caster first and the rest from their teammates in order.
+    fn pay(&mut self, index: usize, cost: u8) {
+        let own = self.wizards[index].mana.min(cost);
+        self.wizards[index].mana -= own;
+        let mut owed = cost - own;
+        if owed == 0 {
+            return;
+        }
+        let teammates: Vec<usize> = self.teammates(self.teams[index]).collect();
+        for teammate in teammates {
+            let wizard = &mut self.wizards[teammate];
+            let paid = wizard.mana.min(owed);
+            wizard.mana -= paid;
+            owed -= paid;
+        }
+    }
+
+    // Moves of wizards that are already dead are ignored.
+    pub fn tick(&mut self, moves: &[ArenaMove]) -> Result<(), ArenaError> {
+        if moves.len() != self.wizards.len() {
+            return Err(ArenaError::WrongMoveCount {
+                expected: self.wizards.len(),
+                got: moves.len(),
+            });
+        }
+        let alive = self.survivors();
+        for &index in &alive {
+            self.check_move(index, moves[index])?;
+        }
+        if self.config.shared_team_mana {
+            self.check_team_mana(moves)?;
+        }
+
+        // Everything is paid for before anything resolves, so a teammate's
+        // Concentrate can't fund a spell in the same turn.
+        for &index in &alive {
+            let cost = self.config.spells.mana_spent(moves[index].action);
+            self.pay(index, cost);
+        }
+
+        let mut damage = vec![0u8; self.wizards.len()];
+        let mut burn = vec![0u8; self.wizards.len()];
+        for &index in &alive {
+            let mv = moves[index];
+            let wizard = &mut self.wizards[index];
+            wizard.gain_mana(
+                self.config.spells.mana_gained(mv.action),
+                self.config.max_mana,
+            );
+
+            // Release uses up the charges even when it hits nobody.
+            let charges = match mv.action {
+                Action::Charge => {
+                    wizard.charges = wizard.charges.saturating_add(1).min(MAX_CHARGES);
+                    0
+                }
+                Action::Release => std::mem::take(&mut wizard.charges),
+                _ => 0,
+            };
+            let bonus = if wizard.empowered { EMPOWER_DAMAGE } else { 0 };
+            if mv.action == Action::BloodMagic {
+                damage[index] = damage[index].saturating_add(BLOOD_MAGIC_HEALTH_COST);
+            }
+            if !is_targeted(mv.action) || self.wizards[mv.target].health == 0 {
+                continue;
+            }
+            let defence = moves[mv.target].action;
+            let mode = self.config.reflect_mode;
+            let reflected = defence == Action::Reflect
+                && !(mv.action == Action::ManaBurn && mode == ReflectMode::AttacksOnly);
+            let hit = if reflected { index } else { mv.target };
+            if defence == Action::Teleport {
+                continue;
+            }
+            if mv.action == Action::ManaBurn {
+                burn[hit] = burn[hit].saturating_add(MANA_BURN_AMOUNT);
+            } else if defence != Action::ManaShield {
+                let mut amount = self.config.spells.damage(mv.action);
+                if mv.action == Action::Release {
+                    amount = amount.saturating_mul(charges);
+                }
+                // Like in a duel, a Release without charges has nothing to empower.
+                if mv.action != Action::Release || charges > 0 {
+                    amount = amount.saturating_add(bonus);
+                }
+                if reflected && !self.config.reflect_blocks {
+                    damage[mv.target] = damage[mv.target].saturating_add(amount / 2);
+                }
+                if reflected && mode == ReflectMode::HalveAndBounce {
+                    amount /= 2;
+                }
+                if reflected {
+                    amount = amount.saturating_add(REFLECT_DAMAGE);
+                }
+                damage[hit] = damage[hit].saturating_add(amount);
+            }
+        }
+
+        for &index in &alive {
+            let wizard = &mut self.wizards[index];
+            wizard.health = wizard.health.saturating_sub(damage[index]);
+            wizard.mana = wizard.mana.saturating_sub(burn[index]);
+            if moves[index].action == Action::Infuse && wizard.health > 0 {
+                wizard.health = wizard
+                    .health
+                    .saturating_add(INFUSE_HEALTH)
+                    .min(self.config.starting_health);
+            }
+            wizard.tick_effects(self.config.starting_health);
+            if self.config.last_stand {
+                wizard.check_last_stand(self.config.max_mana);
+            }
+            wizard.gain_mana(self.config.mana_per_turn, self.config.max_mana);
+            wizard.decay_mana(self.config.mana_decay, self.config.mana_decay_threshold);
+            wizard.empowered = moves[index].action == Action::Empower;
+            wizard.last_action = Some(moves[index].action);
+        }
+        self.turn_count = self.turn_count.saturating_add(1);
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::simulation::{run_batch, simulate, SimulationReport};
+use crate::strategy::Strategy;
+use crate::{GameConfig, GameError, Side, SpellTable};
+use serde::Serialize;
+
+// What `BalanceSweep::best` optimizes for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BalanceObjective {
+    // Left and right win as often as each other.
+    Fairness,
+    // As few draws, mostly double deaths, as possible.
+    FewestTies,
+}
+
+#[derive(Clone, Debug)]
+pub struct SweepEntry {
+    pub spells: SpellTable,
+    pub report: SimulationReport,
+}
+
+impl SweepEntry {
+    // Share of games that ended in a draw.
+    pub fn tie_rate(&self) -> f64 {
+        self.report.draws as f64 / self.report.games.max(1) as f64
+    }
+
+    // Gap between the two sides' win rates, 0 being perfectly fair.
+    pub fn unfairness(&self) -> f64 {
+        let gap = self.report.left_wins.abs_diff(self.report.right_wins);
+        gap as f64 / self.report.games.max(1) as f64
+    }
+
+    fn score(&self, objective: BalanceObjective) -> f64 {
+        match objective {
+            BalanceObjective::Fairness => self.unfairness(),
+            BalanceObjective::FewestTies => self.tie_rate(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BalanceSweep {
+    pub entries: Vec<SweepEntry>,
+}
+
+impl BalanceSweep {
+    // The candidate table scoring best under `objective`, the earliest one on ties.
+    pub fn best(&self, objective: BalanceObjective) -> Option<&SpellTable> {
+        self.entries
+            .iter()
+            .min_by(|a, b| a.score(objective).total_cmp(&b.score(objective)))
+            .map(|entry| &entry.spells)
+    }
+}
+
+/*
+Simulates `games` random games under `config` for every candidate spell table,
+all on the same seed so the tables are compared on equal terms.
+*/
+pub fn sweep(
+    config: &GameConfig,
+    candidates: &[SpellTable],
+    games: u32,
+    seed: u64,
+) -> Result<BalanceSweep, GameError> {
+    let mut result = BalanceSweep::default();
+    for spells in candidates {
+        let config = GameConfig {
+            spells: spells.clone(),
+            ..config.clone()
+        };
+        result.entries.push(SweepEntry {
+            spells: spells.clone(),
+            report: simulate(&config, games, seed)?,
+        });
+    }
+    Ok(result)
+}
+
+// Values to try for each setting, every combination becoming one cell of the matrix.
+#[derive(Clone, Debug)]
+pub struct ConfigGrid {
+    pub starting_mana: Vec<u8>,
+    pub mana_per_turn: Vec<u8>,
+    pub starting_health: Vec<u8>,
+}
+
+// One cell of the who-would-win matrix, the rates summing to 1 whenever a game was played.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+pub struct MatchupCell {
+    pub starting_mana: u8,
+    pub mana_per_turn: u8,
+    pub starting_health: u8,
+    pub left_rate: f64,
+    pub right_rate: f64,
+    pub draw_rate: f64,
+}
+
+impl MatchupCell {
+    // The side winning more often, None on an exact split.
+    pub fn favored(&self) -> Option<Side> {
+        match self.left_rate.total_cmp(&self.right_rate) {
+            std::cmp::Ordering::Greater => Some(Side::Left),
+            std::cmp::Ordering::Less => Some(Side::Right),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/*
+Plays `left` against `right` for `games` games at every point of `grid`,
+everything else taken from `base`. Cells come out starting mana first, then
+mana per turn, then health, and every cell reuses `seed` so neighbouring
+cells only differ by their config. A grid point `base` can't validate with,
+such as starting mana above max_mana, is returned as the error.
+*/
+pub fn who_would_win(
+    base: &GameConfig,
+    grid: &ConfigGrid,
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    games: usize,
+    seed: u64,
+) -> Result<Vec<MatchupCell>, GameError> {
+    let mut cells = Vec::new();
+    for &starting_mana in &grid.starting_mana {
+        for &mana_per_turn in &grid.mana_per_turn {
+            for &starting_health in &grid.starting_health {
+                let config = GameConfig {
+                    starting_mana,
+                    mana_per_turn,
+                    starting_health,
+                    ..base.clone()
+                };
+                let results = run_batch(&config, left, right, games, seed)?;
+                let rate = |winner: Option<Side>| {
+                    let count = results.iter().filter(|r| r.winner == winner).count();
+                    count as f64 / results.len().max(1) as f64
+                };
+                cells.push(MatchupCell {
+                    starting_mana,
+                    mana_per_turn,
+                    starting_health,
+                    left_rate: rate(Some(Side::Left)),
+                    right_rate: rate(Some(Side::Right)),
+                    draw_rate: rate(None),
+                });
+            }
+        }
+    }
+    Ok(cells)
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum EffectKind {
+    // Loses HP at the end of every turn.
+    Poison,
+    // Regains HP at the end of every turn, never above the starting health.
+    Regeneration,
+    // Marker left by the last stand surge so it only happens once per game.
+    LastStandUsed,
+}
+
+/*
+Something lasting on a wizard, applied at the end of every turn after the
+spells have resolved. Several effects, even of the same kind, can coexist.
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    // Turns left including the current one, None for the rest of the game.
+    pub turns: Option<u32>,
+    // HP change every turn, negative for damage.
+    pub health_per_turn: i8,
+}
+
+impl StatusEffect {
+    pub fn new(kind: EffectKind, turns: u32, health_per_turn: i8) -> StatusEffect {
+        StatusEffect {
+            kind,
+            turns: Some(turns),
+            health_per_turn,
+        }
+    }
+
+    // Lasts for the rest of the game and does nothing on its own.
+    pub fn permanent(kind: EffectKind) -> StatusEffect {
+        StatusEffect {
+            kind,
+            turns: None,
+            health_per_turn: 0,
+        }
+    }
+}
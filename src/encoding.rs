@@ -0,0 +1,83 @@
+use crate::{EffectKind, Game, GameConfig, GameError, Side, StatusEffect, Wizard};
+
+/*
+Packs both wizards into the low 24 bits of a u32, for transposition tables and
+thin network packets:
+
+    bits  0..6   left HP       bits  6..12  right HP
+    bits 12..16  left mana     bits 16..20  right mana
+    bit  20      left last stand used
+    bit  21      right last stand used
+    bit  22      left surrendered
+    bit  23      right surrendered
+
+Turn count, history, charges and any other effects are left out.
+*/
+const HEALTH_BITS: u32 = 6;
+const MANA_BITS: u32 = 4;
+
+fn fits(value: u8, bits: u32, field: &'static str) -> Result<u32, GameError> {
+    if (value as u32) < 1 << bits {
+        Ok(value as u32)
+    } else {
+        Err(GameError::EncodingOverflow { field, value })
+    }
+}
+
+fn at_most(value: u8, max: u8, field: &'static str) -> Result<u8, GameError> {
+    if value <= max {
+        Ok(value)
+    } else {
+        Err(GameError::DecodedOutOfRange { field, value, max })
+    }
+}
+
+fn bit(code: u32, index: u32) -> bool {
+    code >> index & 1 == 1
+}
+
+impl Game {
+    pub fn encode(&self) -> Result<u32, GameError> {
+        let left = &self.left_wizard;
+        let right = &self.right_wizard;
+        Ok(fits(left.health, HEALTH_BITS, "left health")?
+            | fits(right.health, HEALTH_BITS, "right health")? << 6
+            | fits(left.mana, MANA_BITS, "left mana")? << 12
+            | fits(right.mana, MANA_BITS, "right mana")? << 16
+            | (left.last_stand_used() as u32) << 20
+            | (right.last_stand_used() as u32) << 21
+            | (left.surrendered as u32) << 22
+            | (right.surrendered as u32) << 23)
+    }
+
+    /*
+    A fresh game under `config` with the wizards set to what `encode` packed into `code`.
+    Fails on HP above starting_health or mana above max_mana, which no game under
+    `config` could have reached.
+    */
+    pub fn decode(code: u32, config: GameConfig) -> Result<Game, GameError> {
+        let mut game = Game::with_config(config)?;
+        let (max_health, max_mana) = (game.config.starting_health, game.config.max_mana);
+        for (index, side) in [Side::Left, Side::Right].into_iter().enumerate() {
+            let (health_field, mana_field) = match side {
+                Side::Left => ("left health", "left mana"),
+                _ => ("right health", "right mana"),
+            };
+            let index = index as u32;
+            let health = at_most((code >> (6 * index) & 0x3f) as u8, max_health, health_field)?;
+            let mana = at_most((code >> (12 + 4 * index) & 0xf) as u8, max_mana, mana_field)?;
+            let wizard: &mut Wizard = game.wizard_mut(side);
+            wizard.health = health;
+            wizard.mana = mana;
+            wizard.effects.clear();
+            if bit(code, 20 + index) {
+                wizard
+                    .effects
+                    .push(StatusEffect::permanent(EffectKind::LastStandUsed));
+            }
+            wizard.surrendered = bit(code, 22 + index);
+        }
+        game.update_result();
+        Ok(game)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Action, Game, GameConfig, GameError, GameStatus, Side};
+
+// `Game::encode` of the position, what an agent sees each step.
+pub type Observation = u32;
+
+// Most mana and HP `Game::encode` has room for.
+const MAX_ENCODED_MANA: u8 = 15;
+const MAX_ENCODED_HEALTH: u8 = 63;
+
+/*
+The duel as a reinforcement learning environment: `reset` starts an episode
+and `step` plays one turn with both agents' actions. Rewards are zero-sum,
+each side's HP change minus the opponent's as a share of starting_health,
+plus 1 for winning and -1 for losing on the step that ends the game.
+Episodes are seeded 0, 1, 2 and so on, which only matters with
+damage_variance on.
+*/
+pub struct GameEnv {
+    config: GameConfig,
+    game: Game,
+    episode: u64,
+}
+
+impl Default for GameEnv {
+    // Mana capped to what an observation can hold, and episodes cut off after 100 turns.
+    fn default() -> Self {
+        GameEnv::new(GameConfig {
+            max_mana: MAX_ENCODED_MANA,
+            max_turns: Some(100),
+            ..GameConfig::default()
+        })
+        .expect("default env config is valid")
+    }
+}
+
+impl GameEnv {
+    // Rejects configs whose positions wouldn't always fit in an Observation.
+    pub fn new(config: GameConfig) -> Result<GameEnv, GameError> {
+        if config.max_mana > MAX_ENCODED_MANA {
+            return Err(GameError::InvalidConfig(
+                "max_mana must be 15 or less to observe",
+            ));
+        }
+        if config.starting_health > MAX_ENCODED_HEALTH {
+            return Err(GameError::InvalidConfig(
+                "starting_health must be 63 or less to observe",
+            ));
+        }
+        let game = Game::with_seed(config.clone(), 0)?;
+        Ok(GameEnv {
+            config,
+            game,
+            episode: 0,
+        })
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn observe(&self) -> Observation {
+        self.game
+            .encode()
+            .expect("`new` keeps HP and mana in encodable range")
+    }
+
+    pub fn reset(&mut self) -> Observation {
+        self.game = Game::with_seed(self.config.clone(), self.episode).expect("validated in `new`");
+        self.episode += 1;
+        self.observe()
+    }
+
+    /*
+    Plays one turn and returns the observation after it, Left's and Right's
+    rewards and whether the episode is over. An illegal action is returned
+    as the error without playing the turn. Call `reset` once done.
+    */
+    pub fn step(
+        &mut self,
+        left_action: Action,
+        right_action: Action,
+    ) -> Result<(Observation, f64, f64, bool), GameError> {
+        let health = |game: &Game, side| game.wizard(side).health() as f64;
+        let lead = |game: &Game| health(game, Side::Left) - health(game, Side::Right);
+        let before = lead(&self.game);
+        self.game.tick(left_action, right_action)?;
+        let mut reward = (lead(&self.game) - before) / self.config.starting_health as f64;
+        match self.game.status() {
+            GameStatus::Winner(Side::Left) => reward += 1.0,
+            GameStatus::Winner(_) => reward -= 1.0,
+            GameStatus::Draw | GameStatus::Ongoing => (),
+        }
+        let done = self.game.result().is_some();
+        Ok((self.observe(), reward, -reward, done))
+    }
+}
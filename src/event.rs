@@ -0,0 +1,20 @@
+use crate::Side;
+use serde::{Deserialize, Serialize};
+
+/*
+One step of a turn's resolution, for driving a spectator client event by event.
+`side` is always the wizard the event happened to.
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameEvent {
+    // `turn` counts from 1.
+    TurnStarted { turn: u32 },
+    DamageDealt { side: Side, amount: u8 },
+    // `side` shielded away an attack.
+    Blocked { side: Side },
+    // `side` bounced `amount` back at the caster.
+    Reflected { side: Side, amount: u8 },
+    ManaChanged { side: Side, delta: i16 },
+    // None for a draw.
+    GameEnded { winner: Option<Side> },
+}
@@ -0,0 +1,1650 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+/*
+Wizard duel
+Simultaneous
+Each wizard starts with 25 HP and 5 mana
+If HP reaches 0, the wizard dies, and they lose
+Mana increases by 1 every turn.
+*/
+
+pub mod arena;
+pub mod balance;
+mod effect;
+mod encoding;
+pub mod env;
+mod event;
+mod hash;
+mod locale;
+mod parse;
+pub mod rating;
+pub mod replay;
+pub mod rng;
+mod rollout;
+mod saturation;
+pub mod schema;
+mod search;
+pub mod simulation;
+mod snapshot;
+mod spells;
+pub mod strategy;
+mod summary;
+pub mod tournament;
+pub mod training;
+pub mod turn_timer;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+
+pub use effect::{EffectKind, StatusEffect};
+pub use event::GameEvent;
+pub use hash::{stable_hash, StableHasher};
+pub use locale::Lang;
+pub use parse::{ActionParser, ParseActionError};
+pub use saturation::SaturationStats;
+pub use search::{Dominated, Favorite, Opening, PositionWeights};
+pub use snapshot::{ActionMix, ActionPreview, GameSnapshot};
+pub use spells::{Spell, SpellTable};
+pub use summary::{GameSummary, WizardStats};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Action {
+    // Deals 2 damage, manaless.
+    Strike,
+    // Deals 3 damage, but costs 1 mana.
+    Fireball,
+    // Deals 5 damage, but costs 2 mana.
+    LightningBolt,
+    // Blocks all incoming damage, costs 1 mana.
+    ManaShield,
+    // If opponent does any attack, the attack is reflected and deals +1 damage. Costs 2 mana.
+    Reflect,
+    // Restores 4 mana (not including the passive gain).
+    Concentrate,
+    // Swaps both wizards' HP once the turn's damage is resolved, costs 5 mana. Two Exchanges cancel out.
+    Exchange,
+    // Burns 3 of the opponent's mana, costs 2 mana. ManaShield doesn't stop it, but Reflect bounces it back.
+    ManaBurn,
+    // Costs 3 HP and restores 5 mana. Can't be cast at 3 HP or less.
+    BloodMagic,
+    // Stores a charge for a later Release, up to MAX_CHARGES. Costs 1 mana.
+    Charge,
+    // Deals 2 damage per stored charge and uses them all up, manaless. A wasted turn without charges.
+    Release,
+    // Dodges every attack and ManaBurn aimed at the caster this turn, nothing to reflect. Costs 2 mana.
+    // Can't be cast two turns in a row.
+    Teleport,
+    // Costs 2 mana. Next turn the opponent commits first and the caster sees their action
+    // before picking, see `Game::foresight`. Two Foresights on the same turn cancel out.
+    Foresight,
+    // Costs 4 mana and restores INFUSE_HEALTH HP, never above starting_health. Can't be reflected.
+    Infuse,
+    // Costs 3 mana. The opponent's next action is forced to repeat the one they played before
+    // this turn, overriding their pick. Fizzles if they have no previous action.
+    Compel,
+    // Costs 2 mana. The caster's attack next turn deals EMPOWER_DAMAGE more, once.
+    // Lost if the next action isn't an attack.
+    Empower,
+    // Plays the next action of the wizard's queued combo, see `Game::queue_combo`.
+    Combo,
+    // Concedes the game to the opponent before anything else resolves. Both surrendering is a draw.
+    Surrender,
+}
+
+// Mana removed from the target of a ManaBurn.
+pub const MANA_BURN_AMOUNT: u8 = 3;
+// HP paid by the caster of BloodMagic.
+pub const BLOOD_MAGIC_HEALTH_COST: u8 = 3;
+// HP restored by Infuse.
+pub const INFUSE_HEALTH: u8 = 3;
+// Charges a wizard can hold, further Charges are wasted.
+pub const MAX_CHARGES: u8 = 3;
+// Extra damage of the attack following an Empower.
+pub const EMPOWER_DAMAGE: u8 = 3;
+// Extra damage of an attack bounced back by Reflect.
+pub const REFLECT_DAMAGE: u8 = 1;
+
+// Illegal picks in a row the game loops re-roll before giving up with the last
+// rejection, so a strategy that keeps picking a move `tick` refuses can't hang them.
+pub(crate) const MAX_REJECTED_PICKS: u32 = 1000;
+
+// Every action in declaration order, for UIs and strategies that need to iterate them.
+// Surrender stays last so the spells can be taken as `ALL_ACTIONS[..ALL_ACTIONS.len() - 1]`.
+pub const ALL_ACTIONS: [Action; 18] = [
+    Action::Strike,
+    Action::Fireball,
+    Action::LightningBolt,
+    Action::ManaShield,
+    Action::Reflect,
+    Action::Concentrate,
+    Action::Exchange,
+    Action::ManaBurn,
+    Action::BloodMagic,
+    Action::Charge,
+    Action::Release,
+    Action::Teleport,
+    Action::Foresight,
+    Action::Infuse,
+    Action::Compel,
+    Action::Empower,
+    Action::Combo,
+    Action::Surrender,
+];
+
+impl Action {
+    pub fn all() -> &'static [Action] {
+        &ALL_ACTIONS
+    }
+
+    // Whether the action deals damage to the opponent.
+    pub fn is_attack(&self) -> bool {
+        matches!(
+            self,
+            Action::Strike | Action::Fireball | Action::LightningBolt | Action::Release
+        )
+    }
+
+    // Whether the action only protects the caster.
+    pub fn is_defensive(&self) -> bool {
+        matches!(
+            self,
+            Action::ManaShield | Action::Reflect | Action::Teleport
+        )
+    }
+
+    // Whether the action is aimed at the opponent.
+    pub fn is_offensive(&self) -> bool {
+        matches!(
+            self,
+            Action::Strike
+                | Action::Fireball
+                | Action::LightningBolt
+                | Action::ManaBurn
+                | Action::Release
+        )
+    }
+
+    pub fn damage_amnt(&self) -> u8 {
+        match self {
+            Action::Strike => 2,
+            Action::Fireball => 3,
+            Action::LightningBolt => 5,
+            Action::ManaShield => 0,
+            Action::Reflect => 0,
+            Action::Concentrate => 0,
+            Action::Exchange => 0,
+            Action::ManaBurn => 0,
+            Action::BloodMagic => 0,
+            Action::Charge => 0,
+            Action::Release => 2,
+            Action::Teleport => 0,
+            Action::Foresight => 0,
+            Action::Infuse => 0,
+            Action::Compel => 0,
+            Action::Empower => 0,
+            Action::Combo => 0,
+            Action::Surrender => 0,
+        }
+    }
+
+    pub fn mana_cost(&self) -> i8 {
+        match self {
+            Action::Strike => 0,
+            Action::Fireball => 1,
+            Action::LightningBolt => 2,
+            Action::ManaShield => 1,
+            Action::Reflect => 2,
+            Action::Concentrate => -4,
+            Action::Exchange => 5,
+            Action::ManaBurn => 2,
+            Action::BloodMagic => -5,
+            Action::Charge => 1,
+            Action::Release => 0,
+            Action::Teleport => 2,
+            Action::Foresight => 2,
+            Action::Infuse => 4,
+            Action::Compel => 3,
+            Action::Empower => 2,
+            Action::Combo => 0,
+            Action::Surrender => 0,
+        }
+    }
+
+    // Stable number of the action for hosts that pass actions as integers, its place in ALL_ACTIONS.
+    pub fn code(&self) -> u8 {
+        ALL_ACTIONS
+            .iter()
+            .position(|action| action == self)
+            .unwrap() as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Action> {
+        ALL_ACTIONS.get(code as usize).copied()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+    Neither,
+}
+
+impl Side {
+    pub const ALL: [Side; 3] = [Side::Left, Side::Right, Side::Neither];
+
+    pub fn opponent(&self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+            Side::Neither => Side::Neither,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameStatus {
+    Ongoing,
+    Winner(Side),
+    Draw,
+}
+
+impl GameStatus {
+    // One of each variant, Winner standing in for every side.
+    pub const ALL: [GameStatus; 3] = [
+        GameStatus::Ongoing,
+        GameStatus::Winner(Side::Left),
+        GameStatus::Draw,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum WinReason {
+    // At least one wizard was brought to 0 HP.
+    Lethal,
+    // The turn limit was reached with both wizards on equal HP.
+    TurnLimit,
+    // The turn limit was reached and the wizard with more HP won.
+    Tiebreak,
+    // A wizard gave up.
+    Surrender,
+    // A wizard dealt the damage total of `WinCondition::DamageDealt`.
+    DamageThreshold,
+}
+
+impl WinReason {
+    pub const ALL: [WinReason; 5] = [
+        WinReason::Lethal,
+        WinReason::TurnLimit,
+        WinReason::Tiebreak,
+        WinReason::Surrender,
+        WinReason::DamageThreshold,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameResult {
+    // None for a draw.
+    pub winner: Option<Side>,
+    pub reason: WinReason,
+    pub turns: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameError {
+    // The wizard on `side` tried to cast `action` without enough mana.
+    NotEnoughMana {
+        side: Side,
+        action: Action,
+        have: u8,
+        need: u8,
+    },
+    // Casting `action` would kill the wizard on `side`.
+    NotEnoughHealth {
+        side: Side,
+        action: Action,
+        have: u8,
+        need: u8,
+    },
+    // `action` isn't in the spellbook of the wizard on `side`.
+    NotInSpellbook {
+        side: Side,
+        action: Action,
+    },
+    // The wizard on `side` played Combo with nothing queued.
+    EmptyCombo {
+        side: Side,
+    },
+    // `action` was cast last turn and can't be cast again yet.
+    OnCooldown {
+        side: Side,
+        action: Action,
+    },
+    // `first_turn_attacks_disabled` is set and `side` attacked on turn one.
+    FirstTurnAttack {
+        side: Side,
+        action: Action,
+    },
+    // `value` doesn't fit in the bits `Game::encode` has for `field`.
+    EncodingOverflow {
+        field: &'static str,
+        value: u8,
+    },
+    // `Game::decode` read a `field` of `value`, above the `max` its config allows.
+    DecodedOutOfRange {
+        field: &'static str,
+        value: u8,
+        max: u8,
+    },
+    // Another tick would push `turn_count` past u32::MAX.
+    TurnOverflow,
+    InvalidConfig(&'static str),
+    // With `strict_arithmetic` on, the move would take `need` from the `field`
+    // of the wizard on `side`, which only has `have`.
+    Underflow {
+        side: Side,
+        field: &'static str,
+        have: u8,
+        need: u8,
+    },
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NotEnoughMana {
+                side,
+                action,
+                have,
+                need,
+            } => write!(
+                f,
+                "{:?} wizard did not have enough mana for {:?} (have {}, need {})",
+                side, action, have, need
+            ),
+            GameError::NotEnoughHealth {
+                side,
+                action,
+                have,
+                need,
+            } => write!(
+                f,
+                "{:?} wizard did not have enough health for {:?} (have {}, need {})",
+                side, action, have, need
+            ),
+            GameError::NotInSpellbook { side, action } => {
+                write!(f, "{:?} wizard can't cast {:?}", side, action)
+            }
+            GameError::EmptyCombo { side } => write!(f, "{:?} wizard has no combo queued", side),
+            GameError::OnCooldown { side, action } => {
+                write!(
+                    f,
+                    "{:?} wizard can't cast {:?} twice in a row",
+                    side, action
+                )
+            }
+            GameError::FirstTurnAttack { side, action } => {
+                write!(f, "{:?} wizard can't open with {:?}", side, action)
+            }
+            GameError::EncodingOverflow { field, value } => {
+                write!(f, "{} of {} is too big to encode", field, value)
+            }
+            GameError::DecodedOutOfRange { field, value, max } => {
+                write!(f, "decoded {} of {} is above {}", field, value, max)
+            }
+            GameError::TurnOverflow => write!(f, "turn count would overflow"),
+            GameError::InvalidConfig(reason) => write!(f, "invalid config: {}", reason),
+            GameError::Underflow {
+                side,
+                field,
+                have,
+                need,
+            } => write!(
+                f,
+                "{:?} wizard's {} would go below 0 (have {}, need {})",
+                side, field, have, need
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+// What Reflect does to the spells it catches. Bounced attacks deal REFLECT_DAMAGE more.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum ReflectMode {
+    // Attacks and ManaBurn bounce back in full.
+    #[default]
+    Full,
+    // Attacks bounce back in full, ManaBurn goes through.
+    AttacksOnly,
+    // Attacks bounce back at half damage rounded down, ManaBurn bounces in full.
+    HalveAndBounce,
+}
+
+// How a game can be won besides a surrender or the turn limit.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum WinCondition {
+    // Bring the opponent to 0 HP.
+    #[default]
+    Lethal,
+    /*
+    Points match: the first wizard to deal this much damage in total wins,
+    and 0 HP still ends the game first. Both reaching it on the same turn is
+    won by whoever dealt more, a draw when even.
+    */
+    DamageDealt(u32),
+}
+
+/*
+Decides a turn where both wizards hit 0 HP. Overkill is the damage a wizard
+took beyond what it took to bring them to 0, credited to their opponent.
+Equal overkill is a draw under both overkill rules.
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum SimultaneousDeathRule {
+    #[default]
+    Draw,
+    // The wizard who dealt more overkill wasted their mana and loses.
+    PenalizeOverkill,
+    // The wizard who dealt more overkill still had damage in flight and wins.
+    OverkillWins,
+    // Never a draw, `Game::coin_flip` picks the winner, the same one every time for a position.
+    CoinFlip,
+}
+
+/*
+Settings for one wizard, for asymmetric class-based matchups and scenario puzzles.
+Concentrate is always castable, in the spellbook or not.
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WizardConfig {
+    pub spellbook: HashSet<Action>,
+    // On the wizard from the start, ticking at the end of the first turn.
+    pub starting_effects: Vec<StatusEffect>,
+    // Up to MAX_CHARGES.
+    pub starting_charges: u8,
+}
+
+impl Default for WizardConfig {
+    fn default() -> Self {
+        WizardConfig {
+            spellbook: ALL_ACTIONS.into_iter().collect(),
+            starting_effects: Vec::new(),
+            starting_charges: 0,
+        }
+    }
+}
+
+// Hashed in ALL_ACTIONS order, so equal spellbooks always hash the same.
+impl Hash for WizardConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for action in ALL_ACTIONS {
+            self.spellbook.contains(&action).hash(state);
+        }
+        self.starting_effects.hash(state);
+        self.starting_charges.hash(state);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub starting_health: u8,
+    pub starting_mana: u8,
+    // Mana gains stop at this value.
+    pub max_mana: u8,
+    // Mana lost at the very end of every turn by a wizard holding more than
+    // `mana_decay_threshold`, after all gains. 0 turns the leak off.
+    pub mana_decay: u8,
+    pub mana_decay_threshold: u8,
+    // Passive gain at the end of every turn. At 0 Concentrate is the only way to get mana,
+    // but Strike is free so games still end.
+    pub mana_per_turn: u8,
+    // Attacks deal their base damage plus or minus up to this much, rolled on the game's seeded RNG.
+    // 0 keeps damage deterministic. Duel only.
+    pub damage_variance: u8,
+    // The first time a wizard is brought down to exactly 1 HP they surge for +2 mana, once per game.
+    pub last_stand: bool,
+    // A Concentrate right after another Concentrate only gives half the mana, rounded down.
+    // Duel only.
+    pub channeling_limit: bool,
+    // ManaShield costs another 1 mana per 2 damage it absorbs, paid as the attack lands.
+    // Damage the wizard can't pay for gets through. Duel only.
+    pub reactive_shield: bool,
+    // Turn one can't be offensive, so a high starting_mana can't open with a LightningBolt.
+    // Duel only.
+    pub first_turn_attacks_disabled: bool,
+    // An attack the wizard can't fully afford may be cast with whatever mana is left,
+    // its damage scaled down by the share of the cost paid, rounded down. Duel only.
+    pub partial_casting: bool,
+    // Arena teammates draw from one pool of mana instead of their own.
+    pub shared_team_mana: bool,
+    // Once this many turns have been played the game ends, the healthier wizard winning.
+    // Duel only, an arena runs until at most one team is left standing.
+    pub max_turns: Option<u32>,
+    // Duel only.
+    pub simultaneous_death: SimultaneousDeathRule,
+    // Duel only.
+    pub win_condition: WinCondition,
+    pub reflect_mode: ReflectMode,
+    // Whether Reflect also shields the reflector. Without it the reflector still takes
+    // half the attack's damage, rounded down, on top of bouncing it.
+    pub reflect_blocks: bool,
+    // Rejects moves that would take mana the wizard doesn't have as GameError::Underflow
+    // instead of clamping, for catching logic bugs in development: partial casts, and a
+    // ManaBurn at a wizard holding less than MANA_BURN_AMOUNT before the turn. Checked by
+    // `can_cast` like every other rule. Lethal damage still stops at 0 HP. Duel only.
+    pub strict_arithmetic: bool,
+    pub spells: SpellTable,
+    // Ignored by the arena.
+    pub left_wizard: WizardConfig,
+    pub right_wizard: WizardConfig,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            starting_health: 25,
+            starting_mana: 1,
+            max_mana: u8::MAX,
+            mana_per_turn: 1,
+            mana_decay: 0,
+            mana_decay_threshold: 0,
+            damage_variance: 0,
+            last_stand: false,
+            channeling_limit: false,
+            reactive_shield: false,
+            partial_casting: false,
+            first_turn_attacks_disabled: false,
+            shared_team_mana: false,
+            max_turns: None,
+            simultaneous_death: SimultaneousDeathRule::Draw,
+            win_condition: WinCondition::Lethal,
+            reflect_mode: ReflectMode::Full,
+            reflect_blocks: true,
+            strict_arithmetic: false,
+            spells: SpellTable::default(),
+            left_wizard: WizardConfig::default(),
+            right_wizard: WizardConfig::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    // Short fingerprint of the config, stable across runs so it can be printed and compared.
+    pub fn config_hash(&self) -> u64 {
+        stable_hash(self)
+    }
+
+    pub fn wizard(&self, side: Side) -> &WizardConfig {
+        match side {
+            Side::Left => &self.left_wizard,
+            Side::Right => &self.right_wizard,
+            Side::Neither => panic!("Side::Neither has no wizard"),
+        }
+    }
+
+    // Rejects configs that would make a degenerate game.
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.starting_health == 0 {
+            return Err(GameError::InvalidConfig("starting_health must be above 0"));
+        }
+        if self.starting_mana > self.max_mana {
+            return Err(GameError::InvalidConfig(
+                "starting_mana can't be above max_mana",
+            ));
+        }
+        let charges = [&self.left_wizard, &self.right_wizard]
+            .iter()
+            .any(|wizard| wizard.starting_charges > MAX_CHARGES);
+        if charges {
+            return Err(GameError::InvalidConfig(
+                "starting_charges can't be above MAX_CHARGES",
+            ));
+        }
+        self.spells.validate()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Wizard {
+    health: u8,
+    mana: u8,
+    effects: Vec<StatusEffect>,
+    surrendered: bool,
+    // Damage taken beyond 0 HP.
+    overkill: u8,
+    // Damage this wizard's spells landed on the opponent, reflections included.
+    damage_dealt: u32,
+    // HP actually lost and regained, from any source. Exchange counts on both sides.
+    damage_taken: u32,
+    healing_done: u32,
+    // Mana paid for spells, and the part of it `Game::wasted_cast` says went nowhere.
+    mana_spent: u32,
+    mana_wasted: u32,
+    last_action: Option<Action>,
+    charges: u8,
+    // What a Compel forces the wizard to play next turn.
+    compelled: Option<Action>,
+    // Whether the wizard cast Empower last turn, so its attack this turn hits harder.
+    empowered: bool,
+    // What `step_with` plays for the wizard next instead of asking its strategy,
+    // and whether an illegal one falls back to the strategy. See `force_next_action`.
+    forced: Option<(Action, bool)>,
+    // Actions still to come from the current combo, cleared when the wizard takes damage.
+    queue: VecDeque<Action>,
+}
+
+impl Wizard {
+    fn new(config: &GameConfig) -> Wizard {
+        Wizard {
+            health: config.starting_health,
+            mana: config.starting_mana,
+            effects: Vec::new(),
+            surrendered: false,
+            overkill: 0,
+            damage_dealt: 0,
+            damage_taken: 0,
+            healing_done: 0,
+            mana_spent: 0,
+            mana_wasted: 0,
+            last_action: None,
+            charges: 0,
+            compelled: None,
+            empowered: false,
+            forced: None,
+            queue: VecDeque::new(),
+        }
+    }
+
+    // Books the difference from `before` to the current HP as damage taken or healing.
+    fn record_health_change(&mut self, before: u8) {
+        if self.health < before {
+            self.damage_taken = self
+                .damage_taken
+                .saturating_add((before - self.health) as u32);
+        } else {
+            self.healing_done = self
+                .healing_done
+                .saturating_add((self.health - before) as u32);
+        }
+    }
+
+    // The scenario setup from `wizard`, duels only so the arena starts from `new`.
+    fn with_setup(mut self, wizard: &WizardConfig) -> Wizard {
+        self.effects = wizard.starting_effects.clone();
+        self.charges = wizard.starting_charges;
+        self
+    }
+
+    pub fn health(&self) -> u8 {
+        self.health
+    }
+
+    pub fn mana(&self) -> u8 {
+        self.mana
+    }
+
+    pub fn last_stand_used(&self) -> bool {
+        self.has_effect(EffectKind::LastStandUsed)
+    }
+
+    pub fn effects(&self) -> &[StatusEffect] {
+        &self.effects
+    }
+
+    pub fn has_effect(&self, kind: EffectKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn surrendered(&self) -> bool {
+        self.surrendered
+    }
+
+    pub fn damage_dealt(&self) -> u32 {
+        self.damage_dealt
+    }
+
+    // What the wizard did last turn, None before the first turn.
+    pub fn last_action(&self) -> Option<Action> {
+        self.last_action
+    }
+
+    // Charges stored for the next Release.
+    pub fn charges(&self) -> u8 {
+        self.charges
+    }
+
+    // The action a Compel forces on the wizard next turn, if any.
+    pub fn compelled(&self) -> Option<Action> {
+        self.compelled
+    }
+
+    pub fn queue(&self) -> &VecDeque<Action> {
+        &self.queue
+    }
+
+    fn gain_mana(&mut self, mana: u8, max_mana: u8) {
+        self.mana = self.mana.saturating_add(mana).min(max_mana);
+    }
+
+    fn decay_mana(&mut self, decay: u8, threshold: u8) {
+        if self.mana > threshold {
+            self.mana = self.mana.saturating_sub(decay);
+        }
+    }
+
+    fn check_last_stand(&mut self, max_mana: u8) {
+        if self.health == 1 && !self.last_stand_used() {
+            self.gain_mana(2, max_mana);
+            self.effects
+                .push(StatusEffect::permanent(EffectKind::LastStandUsed));
+        }
+    }
+
+    // Applies every effect's HP change once, then drops the ones that ran out.
+    // A dead wizard stays dead.
+    fn tick_effects(&mut self, max_health: u8) {
+        for effect in &mut self.effects {
+            if self.health > 0 {
+                let change = effect.health_per_turn;
+                self.health = if change < 0 {
+                    self.health.saturating_sub(change.unsigned_abs())
+                } else {
+                    self.health.saturating_add(change as u8).min(max_health)
+                };
+            }
+            if let Some(turns) = &mut effect.turns {
+                *turns = turns.saturating_sub(1);
+            }
+        }
+        self.effects.retain(|effect| effect.turns != Some(0));
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Game {
+    left_wizard: Wizard,
+    right_wizard: Wizard,
+    turn_count: u32,
+    config: GameConfig,
+    // What happened during the last tick, in resolution order.
+    events: Vec<GameEvent>,
+    // Only used for game randomness such as damage variance.
+    rng: StdRng,
+    // Every (left, right) action pair played so far.
+    history: Vec<(Action, Action)>,
+    // `compute_result` as of the last change to the wizards or the turn count.
+    result: Option<GameResult>,
+    saturation: SaturationStats,
+    // An action locked in for the coming turn through `commit`.
+    committed: Option<(Side, Action)>,
+    rollout_cache: rollout::RolloutCache,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::with_config(GameConfig::default()).expect("default config is valid")
+    }
+
+    pub fn with_config(config: GameConfig) -> Result<Game, GameError> {
+        Game::with_seed(config, 0)
+    }
+
+    // Same as `with_config`, with `seed` driving any randomness the config turns on.
+    pub fn with_seed(config: GameConfig, seed: u64) -> Result<Game, GameError> {
+        config.validate()?;
+        let mut game = Game {
+            left_wizard: Wizard::new(&config).with_setup(&config.left_wizard),
+            right_wizard: Wizard::new(&config).with_setup(&config.right_wizard),
+            turn_count: 0,
+            config,
+            events: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            history: Vec::new(),
+            result: None,
+            saturation: SaturationStats::default(),
+            committed: None,
+            rollout_cache: Default::default(),
+        };
+        // A turn limit of 0 ends the game before it starts.
+        game.update_result();
+        Ok(game)
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    // Panics on Side::Neither, there is no wizard to return.
+    pub fn wizard(&self, side: Side) -> &Wizard {
+        match side {
+            Side::Left => &self.left_wizard,
+            Side::Right => &self.right_wizard,
+            Side::Neither => panic!("Side::Neither has no wizard"),
+        }
+    }
+
+    pub fn turn_count(&self) -> u32 {
+        self.turn_count
+    }
+
+    fn wizard_mut(&mut self, side: Side) -> &mut Wizard {
+        match side {
+            Side::Left => &mut self.left_wizard,
+            Side::Right => &mut self.right_wizard,
+            Side::Neither => panic!("Side::Neither has no wizard"),
+        }
+    }
+
+    /*
+    Stable hash of both wizards (HP, mana, status effects and the rest of
+    their state) and the turn count, for transposition tables and compact
+    equality checks. The config isn't part of it, only compare fingerprints
+    of games with the same config.
+    */
+    pub fn fingerprint(&self) -> u64 {
+        stable_hash(&(&self.left_wizard, &self.right_wizard, self.turn_count))
+    }
+
+    pub fn left(&self) -> &Wizard {
+        &self.left_wizard
+    }
+
+    pub fn right(&self) -> &Wizard {
+        &self.right_wizard
+    }
+
+    // Every (left, right) action pair played so far, oldest first.
+    pub fn history(&self) -> &[(Action, Action)] {
+        &self.history
+    }
+
+    // The last `n` actions `side` played, oldest first.
+    pub fn recent_actions(&self, side: Side, n: usize) -> Vec<Action> {
+        let start = self.history.len().saturating_sub(n);
+        self.history[start..]
+            .iter()
+            .map(|&(left, right)| match side {
+                Side::Left => left,
+                Side::Right => right,
+                Side::Neither => panic!("Side::Neither has no actions"),
+            })
+            .collect()
+    }
+
+    // Events from the most recent tick, in the order they resolved.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    // Clamped HP and mana arithmetic over the whole game so far.
+    pub fn saturation(&self) -> SaturationStats {
+        self.saturation
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    fn emit_mana_change(&mut self, side: Side, before: u8) {
+        let delta = self.wizard(side).mana as i16 - before as i16;
+        if delta != 0 {
+            self.emit(GameEvent::ManaChanged { side, delta });
+        }
+    }
+
+    /*
+    The HP and mana helpers below panic on Side::Neither through `wizard`, like
+    every other per-wizard accessor. Nothing should ever aim a spell at
+    Neither, and a bug that did would otherwise vanish without a trace.
+    */
+    fn damage_wizard(&mut self, side: Side, damage: u8) {
+        let health = self.wizard(side).health;
+        if damage > health {
+            self.saturation.health_floor += 1;
+        }
+        let wizard = self.wizard_mut(side);
+        wizard.overkill = wizard
+            .overkill
+            .saturating_add(damage.saturating_sub(wizard.health));
+        wizard.health = wizard.health.saturating_sub(damage);
+        wizard.record_health_change(health);
+        // A hit that rounds down to nothing, like Reflect's share of a 1 damage spell, isn't an event.
+        if damage > 0 {
+            self.emit(GameEvent::DamageDealt {
+                side,
+                amount: damage,
+            });
+        }
+    }
+
+    // Damage from a spell cast by `side`'s opponent, credited to them. Breaks `side`'s combo.
+    fn hit(&mut self, side: Side, damage: u8) {
+        self.damage_wizard(side, damage);
+        if damage > 0 {
+            self.wizard_mut(side).queue.clear();
+        }
+        let attacker = self.wizard_mut(side.opponent());
+        attacker.damage_dealt = attacker.damage_dealt.saturating_add(damage as u32);
+    }
+
+    fn add_mana(&mut self, side: Side, mana: u8) {
+        let max_mana = self.config.max_mana;
+        let before = self.wizard(side).mana;
+        if before as u16 + mana as u16 > max_mana as u16 {
+            self.saturation.mana_cap += 1;
+        }
+        self.wizard_mut(side).gain_mana(mana, max_mana);
+        self.emit_mana_change(side, before);
+    }
+
+    fn remove_mana(&mut self, side: Side, mana_cost: u8) {
+        let before = self.wizard(side).mana;
+        if mana_cost > before {
+            self.saturation.mana_floor += 1;
+        }
+        let wizard = self.wizard_mut(side);
+        wizard.mana = wizard.mana.saturating_sub(mana_cost);
+        self.emit_mana_change(side, before);
+    }
+
+    pub fn game_completed(&self) -> (bool, Side) {
+        match self.status() {
+            GameStatus::Ongoing => (false, Side::Neither),
+            GameStatus::Winner(side) => (true, side),
+            GameStatus::Draw => (true, Side::Neither),
+        }
+    }
+
+    pub fn status(&self) -> GameStatus {
+        match self.result() {
+            None => GameStatus::Ongoing,
+            Some(GameResult {
+                winner: Some(side), ..
+            }) => GameStatus::Winner(side),
+            Some(_) => GameStatus::Draw,
+        }
+    }
+
+    // How the game ended, or None while it is still being played.
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    // Called after anything that can end the game, so `result` and `status` stay cheap.
+    fn update_result(&mut self) {
+        self.result = self.compute_result();
+    }
+
+    fn compute_result(&self) -> Option<GameResult> {
+        let left = self.left_wizard.health;
+        let right = self.right_wizard.health;
+        let (winner, reason) = if self.left_wizard.surrendered || self.right_wizard.surrendered {
+            let winner = match (self.left_wizard.surrendered, self.right_wizard.surrendered) {
+                (true, true) => None,
+                (true, false) => Some(Side::Right),
+                _ => Some(Side::Left),
+            };
+            (winner, WinReason::Surrender)
+        } else if left == 0 || right == 0 {
+            let winner = match (left, right) {
+                (0, 0) => self.simultaneous_death_winner(),
+                (0, _) => Some(Side::Right),
+                _ => Some(Side::Left),
+            };
+            (winner, WinReason::Lethal)
+        } else if let Some(winner) = self.damage_threshold_winner() {
+            (winner, WinReason::DamageThreshold)
+        } else if self
+            .config
+            .max_turns
+            .is_some_and(|max_turns| self.turn_count >= max_turns)
+        {
+            // Out of turns, the healthier wizard takes it.
+            match left.cmp(&right) {
+                Ordering::Greater => (Some(Side::Left), WinReason::Tiebreak),
+                Ordering::Less => (Some(Side::Right), WinReason::Tiebreak),
+                Ordering::Equal => (None, WinReason::TurnLimit),
+            }
+        } else {
+            return None;
+        };
+        Some(GameResult {
+            winner,
+            reason,
+            turns: self.turn_count,
+        })
+    }
+
+    // Some(None) for a draw when both reached the threshold with the same total.
+    fn damage_threshold_winner(&self) -> Option<Option<Side>> {
+        let WinCondition::DamageDealt(threshold) = self.config.win_condition else {
+            return None;
+        };
+        let left = self.left_wizard.damage_dealt;
+        let right = self.right_wizard.damage_dealt;
+        if left < threshold && right < threshold {
+            return None;
+        }
+        Some(match left.cmp(&right) {
+            Ordering::Greater => Some(Side::Left),
+            Ordering::Less => Some(Side::Right),
+            Ordering::Equal => None,
+        })
+    }
+
+    /*
+    Deterministic coin flip for tiebreaks, seeded from the position's
+    fingerprint and turn count so the same tie always breaks the same way
+    without an RNG being threaded through. Not for game randomness, that
+    comes from the game's seeded RNG.
+    */
+    fn coin_flip(&self) -> Side {
+        match stable_hash(&(self.fingerprint(), self.turn_count, "coin flip")) % 2 {
+            0 => Side::Left,
+            _ => Side::Right,
+        }
+    }
+
+    fn simultaneous_death_winner(&self) -> Option<Side> {
+        // Left dealt the overkill Right took and the other way around.
+        let dealt_by_left = self.right_wizard.overkill;
+        let dealt_by_right = self.left_wizard.overkill;
+        let more_overkill = match dealt_by_left.cmp(&dealt_by_right) {
+            Ordering::Greater => Some(Side::Left),
+            Ordering::Less => Some(Side::Right),
+            Ordering::Equal => None,
+        };
+        match self.config.simultaneous_death {
+            SimultaneousDeathRule::Draw => None,
+            SimultaneousDeathRule::PenalizeOverkill => more_overkill.map(|side| side.opponent()),
+            SimultaneousDeathRule::OverkillWins => more_overkill,
+            SimultaneousDeathRule::CoinFlip => Some(self.coin_flip()),
+        }
+    }
+
+    // Whether `tick` would accept `action` from the wizard on `side` right now.
+    pub fn can_cast(&self, side: Side, action: Action) -> bool {
+        self.check_legal(side, action).is_ok()
+    }
+
+    pub fn available_actions(&self, side: Side) -> Vec<Action> {
+        ALL_ACTIONS
+            .iter()
+            .copied()
+            .filter(|&action| self.can_cast(side, action))
+            .collect()
+    }
+
+    // Every pairing of a legal Left action with a legal Right action, Left's in the outer loop.
+    pub fn legal_action_pairs(&self) -> Vec<(Action, Action)> {
+        let rights = self.available_actions(Side::Right);
+        self.available_actions(Side::Left)
+            .into_iter()
+            .flat_map(|left| rights.iter().map(move |&right| (left, right)))
+            .collect()
+    }
+
+    // Plays a turn on a copy of the game, leaving this one untouched.
+    pub fn simulate_tick(
+        &self,
+        leftaction: Action,
+        rightaction: Action,
+    ) -> Result<Game, GameError> {
+        let mut next = self.clone();
+        next.tick(leftaction, rightaction)?;
+        Ok(next)
+    }
+
+    // Every legal action for `side` paired with the mana they would start next turn with.
+    // The opponent is assumed to Concentrate, which never touches the caster's mana.
+    pub fn legal_moves_with_preview(&self, side: Side) -> Vec<(Action, u8)> {
+        self.available_actions(side)
+            .into_iter()
+            .filter_map(|action| {
+                let next = match side {
+                    Side::Left => self.simulate_tick(action, Action::Concentrate),
+                    _ => self.simulate_tick(Action::Concentrate, action),
+                };
+                next.ok().map(|next| (action, next.wizard(side).mana))
+            })
+            .collect()
+    }
+
+    // Base damage shifted by up to `damage_variance` either way, never below 0.
+    fn roll_damage(&mut self, base: u8) -> u8 {
+        let variance = self.config.damage_variance as i16;
+        if variance == 0 {
+            return base;
+        }
+        let roll = self.rng.random_range(-variance..=variance);
+        (base as i16 + roll).clamp(0, u8::MAX as i16) as u8
+    }
+
+    // Reactive ManaShield: every 2 damage absorbed costs 1 more mana, and whatever
+    // the shielded wizard can't pay for gets through.
+    fn absorb_with_mana(&mut self, side: Side, damage: u8) {
+        let cost = damage / 2;
+        let paid = cost.min(self.wizard(side).mana);
+        self.remove_mana(side, paid);
+        let absorbed = if paid == cost { damage } else { paid * 2 };
+        self.emit(GameEvent::Blocked { side });
+        if absorbed < damage {
+            self.hit(side, damage - absorbed);
+        }
+    }
+
+    fn resolve_attack(&mut self, attacker_side: Side, defender: Action, damage: u8) {
+        let defender_side = attacker_side.opponent();
+        let mut damage = self.roll_damage(damage);
+        if defender == Action::Reflect {
+            let reflector_share = damage / 2;
+            if self.config.reflect_mode == ReflectMode::HalveAndBounce {
+                damage /= 2;
+            }
+            damage = damage.saturating_add(REFLECT_DAMAGE);
+            self.emit(GameEvent::Reflected {
+                side: defender_side,
+                amount: damage,
+            });
+            self.hit(attacker_side, damage);
+            if !self.config.reflect_blocks {
+                self.hit(defender_side, reflector_share);
+            }
+        } else if defender == Action::ManaShield && self.config.reactive_shield {
+            self.absorb_with_mana(defender_side, damage);
+        } else if defender == Action::ManaShield || defender == Action::Teleport {
+            self.emit(GameEvent::Blocked {
+                side: defender_side,
+            });
+        } else {
+            self.hit(defender_side, damage);
+        }
+    }
+
+    // `paid` is the mana the attacker actually spent, less than the cost for a partial cast.
+    fn evaluate(&mut self, attacker_side: Side, attacker: Action, defender: Action, paid: u8) {
+        let defender_side = attacker_side.opponent();
+        let cost = self.config.spells.mana_spent(attacker);
+        let mut damage = self.config.spells.damage(attacker);
+        if paid < cost {
+            damage = (damage as u16 * paid as u16 / cost as u16) as u8;
+        }
+        let bonus = if self.wizard(attacker_side).empowered {
+            EMPOWER_DAMAGE
+        } else {
+            0
+        };
+        match attacker {
+            Action::Strike | Action::Fireball | Action::LightningBolt => {
+                self.resolve_attack(attacker_side, defender, damage.saturating_add(bonus))
+            }
+            Action::Charge => {
+                let wizard = self.wizard_mut(attacker_side);
+                wizard.charges = wizard.charges.saturating_add(1).min(MAX_CHARGES);
+            }
+            Action::Release => {
+                let charges = std::mem::take(&mut self.wizard_mut(attacker_side).charges);
+                if charges > 0 {
+                    let damage = damage.saturating_mul(charges).saturating_add(bonus);
+                    self.resolve_attack(attacker_side, defender, damage);
+                }
+            }
+            Action::Concentrate => {
+                let mut gain = self.config.spells.mana_gained(attacker);
+                let channeling =
+                    self.wizard(attacker_side).last_action == Some(Action::Concentrate);
+                if self.config.channeling_limit && channeling {
+                    gain /= 2;
+                }
+                self.add_mana(attacker_side, gain)
+            }
+            Action::BloodMagic => {
+                self.damage_wizard(attacker_side, BLOOD_MAGIC_HEALTH_COST);
+                let gain = self.config.spells.mana_gained(attacker);
+                self.add_mana(attacker_side, gain)
+            }
+            Action::Compel => {
+                // `last_action` still holds the turn before this one.
+                let repeat = self.wizard(defender_side).last_action;
+                self.wizard_mut(defender_side).compelled = repeat;
+            }
+            Action::ManaBurn => {
+                if defender == Action::Reflect
+                    && self.config.reflect_mode != ReflectMode::AttacksOnly
+                {
+                    self.emit(GameEvent::Reflected {
+                        side: defender_side,
+                        amount: MANA_BURN_AMOUNT,
+                    });
+                    self.remove_mana(attacker_side, MANA_BURN_AMOUNT);
+                } else if defender != Action::Teleport {
+                    self.remove_mana(defender_side, MANA_BURN_AMOUNT);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /*
+    Whether `action` by `side` was wasted in the turn being resolved: offence
+    shielded, reflected or dodged, and defences cast when nothing came in.
+    */
+    pub(crate) fn wasted_cast(&self, side: Side, action: Action, reply: Action) -> bool {
+        let target = side.opponent();
+        if action.is_offensive() {
+            reply == Action::Teleport
+                || self.events.iter().any(|event| {
+                    matches!(event, GameEvent::Blocked { side } | GameEvent::Reflected { side, .. } if *side == target)
+                })
+        } else {
+            action.is_defensive() && !reply.is_offensive()
+        }
+    }
+
+    // Done once per turn rather than in `evaluate`, so the swap lands after both sides'
+    // damage no matter who cast it, and a mutual Exchange can't swap twice.
+    fn resolve_exchange(&mut self, leftaction: Action, rightaction: Action) {
+        if (leftaction == Action::Exchange) != (rightaction == Action::Exchange) {
+            let (left, right) = (self.left_wizard.health, self.right_wizard.health);
+            std::mem::swap(&mut self.left_wizard.health, &mut self.right_wizard.health);
+            self.left_wizard.record_health_change(left);
+            self.right_wizard.record_health_change(right);
+        }
+    }
+
+    // After damage and Exchange like `resolve_exchange`, so whether the heal hits the cap
+    // doesn't depend on which side is evaluated first. A wizard already at 0 HP stays down.
+    fn resolve_infuse(&mut self, side: Side, action: Action) {
+        let max_health = self.config.starting_health;
+        let wizard = self.wizard_mut(side);
+        if action == Action::Infuse && wizard.health > 0 {
+            let before = wizard.health;
+            wizard.health = wizard.health.saturating_add(INFUSE_HEALTH).min(max_health);
+            wizard.record_health_change(before);
+        }
+    }
+
+    fn tick_effects(&mut self, side: Side) {
+        let max_health = self.config.starting_health;
+        let before = self.wizard(side).health;
+        self.wizard_mut(side).tick_effects(max_health);
+        self.wizard_mut(side).record_health_change(before);
+        let after = self.wizard(side).health;
+        if after < before {
+            self.emit(GameEvent::DamageDealt {
+                side,
+                amount: before - after,
+            });
+        }
+    }
+
+    // Puts `effect` on the wizard on `side`, starting from the end of the next turn.
+    pub fn apply_effect(&mut self, side: Side, effect: StatusEffect) {
+        self.wizard_mut(side).effects.push(effect);
+    }
+
+    // Runs after damage resolution, so a wizard knocked to 1 HP this turn surges immediately.
+    fn check_last_stand(&mut self, side: Side) {
+        if self.config.last_stand {
+            let max_mana = self.config.max_mana;
+            let before = self.wizard(side).mana;
+            self.wizard_mut(side).check_last_stand(max_mana);
+            self.emit_mana_change(side, before);
+        }
+    }
+
+    fn check_legal(&self, side: Side, action: Action) -> Result<(), GameError> {
+        if action == Action::Combo {
+            let next = self.wizard(side).queue.front().copied();
+            return match next {
+                Some(next) if next != Action::Combo => self.check_legal(side, next),
+                _ => Err(GameError::EmptyCombo { side }),
+            };
+        }
+        // Concentrate is the fallback for a mana-starved wizard, so no spell table
+        // or mana level can make it illegal.
+        if action == Action::Concentrate {
+            return Ok(());
+        }
+        if !self.config.wizard(side).spellbook.contains(&action) {
+            return Err(GameError::NotInSpellbook { side, action });
+        }
+        let wizard = self.wizard(side);
+        if action == Action::Teleport && wizard.last_action == Some(Action::Teleport) {
+            return Err(GameError::OnCooldown { side, action });
+        }
+        if self.config.first_turn_attacks_disabled && self.turn_count == 0 && action.is_offensive()
+        {
+            return Err(GameError::FirstTurnAttack { side, action });
+        }
+        let need = self.config.spells.mana_spent(action);
+        let strict = self.config.strict_arithmetic;
+        let partial = self.config.partial_casting && action.is_attack() && wizard.mana > 0;
+        if wizard.mana < need && partial && strict {
+            return Err(GameError::Underflow {
+                side,
+                field: "mana",
+                have: wizard.mana,
+                need,
+            });
+        }
+        if wizard.mana < need && !partial {
+            return Err(GameError::NotEnoughMana {
+                side,
+                action,
+                have: wizard.mana,
+                need,
+            });
+        }
+        let target = self.wizard(side.opponent());
+        if strict && action == Action::ManaBurn && target.mana < MANA_BURN_AMOUNT {
+            return Err(GameError::Underflow {
+                side: side.opponent(),
+                field: "mana",
+                have: target.mana,
+                need: MANA_BURN_AMOUNT,
+            });
+        }
+        // BloodMagic can't be used to kill yourself.
+        if action == Action::BloodMagic && wizard.health <= BLOOD_MAGIC_HEALTH_COST {
+            return Err(GameError::NotEnoughHealth {
+                side,
+                action,
+                have: wizard.health,
+                need: BLOOD_MAGIC_HEALTH_COST + 1,
+            });
+        }
+        Ok(())
+    }
+
+    /*
+    Both actions resolve simultaneously, a turn plays the same with the
+    wizards' sides swapped. Costs, and what a partial cast can pay, come from
+    the mana each wizard had before the turn. Spells are then evaluated in
+    fixed steps rather than side by side wherever saturating at 0 or a cap
+    would make the order matter: mana gains before ManaBurns, then Exchange,
+    then Infuse. Within a step Left goes first, which only ever affects the
+    order of the events.
+    */
+    fn resolve(&mut self, leftaction: Action, rightaction: Action) {
+        // Only short with partial_casting on, `check_legal` rejects it otherwise.
+        let left_paid = self
+            .left_wizard
+            .mana
+            .min(self.config.spells.mana_spent(leftaction));
+        let right_paid = self
+            .right_wizard
+            .mana
+            .min(self.config.spells.mana_spent(rightaction));
+        self.remove_mana(Side::Left, left_paid);
+        self.remove_mana(Side::Right, right_paid);
+        let casts = [
+            (Side::Left, leftaction, rightaction, left_paid),
+            (Side::Right, rightaction, leftaction, right_paid),
+        ];
+        // ManaBurns go last and drain whatever the turn's mana gains left, the way the
+        // arena does it, so a Concentrate can't dodge one by being evaluated second.
+        for burns in [false, true] {
+            for (side, action, reply, paid) in casts {
+                if (action == Action::ManaBurn) == burns {
+                    self.evaluate(side, action, reply, paid);
+                }
+            }
+        }
+        for (side, action, reply, paid) in casts {
+            let wasted = self.wasted_cast(side, action, reply);
+            let wizard = self.wizard_mut(side);
+            wizard.mana_spent = wizard.mana_spent.saturating_add(paid as u32);
+            if wasted {
+                wizard.mana_wasted = wizard.mana_wasted.saturating_add(paid as u32);
+            }
+        }
+        self.resolve_exchange(leftaction, rightaction);
+        self.resolve_infuse(Side::Left, leftaction);
+        self.resolve_infuse(Side::Right, rightaction);
+        self.tick_effects(Side::Left);
+        self.tick_effects(Side::Right);
+        self.check_last_stand(Side::Left);
+        self.check_last_stand(Side::Right);
+        self.add_mana(Side::Left, self.config.mana_per_turn);
+        self.add_mana(Side::Right, self.config.mana_per_turn);
+        self.decay_mana(Side::Left);
+        self.decay_mana(Side::Right);
+    }
+
+    fn decay_mana(&mut self, side: Side) {
+        let decay = self.config.mana_decay;
+        let threshold = self.config.mana_decay_threshold;
+        let before = self.wizard(side).mana;
+        self.wizard_mut(side).decay_mana(decay, threshold);
+        self.emit_mana_change(side, before);
+    }
+
+    /*
+    Queues `actions` for the wizard on `side` to play one per turn through
+    Action::Combo, replacing any combo already queued. Taking damage from
+    the opponent clears the queue, paying BloodMagic's HP doesn't. Each queued
+    action is checked for legality on the turn it's played.
+    */
+    pub fn queue_combo(&mut self, side: Side, actions: &[Action]) {
+        self.wizard_mut(side).queue = actions.iter().copied().collect();
+    }
+
+    // Debug builds stop on a wizard outside 0..=starting_health HP or 0..=max_mana mana,
+    // which the saturating arithmetic is meant to rule out.
+    fn check_bounds(&self) {
+        for side in [Side::Left, Side::Right] {
+            let wizard = self.wizard(side);
+            debug_assert!(
+                wizard.health <= self.config.starting_health && wizard.mana <= self.config.max_mana,
+                "{:?} wizard out of bounds on turn {}: {} HP (max {}), {} mana (max {}), after {:?}",
+                side,
+                self.turn_count,
+                wizard.health,
+                self.config.starting_health,
+                wizard.mana,
+                self.config.max_mana,
+                self.history.last()
+            );
+        }
+    }
+
+    // Whether the wizard on `side` cast Foresight last turn without the opponent cancelling it.
+    pub fn has_foresight(&self, side: Side) -> bool {
+        self.wizard(side).last_action == Some(Action::Foresight)
+            && self.wizard(side.opponent()).last_action != Some(Action::Foresight)
+    }
+
+    /*
+    Locks in `action` as what the wizard on `side` plays this turn, before
+    the other side picks. Hosts taking moves one at a time (over the network,
+    or from two strategies) ask the opponent of a `has_foresight` wizard
+    first and commit their move, so foresight never has to wait on itself.
+    `step_with` does this on its own.
+    */
+    pub fn commit(&mut self, side: Side, action: Action) {
+        self.committed = Some((side, action));
+    }
+
+    // The opponent's committed action, for a wizard on `side` with foresight.
+    pub fn foresight(&self, side: Side) -> Option<Action> {
+        match self.committed {
+            Some((committed, action))
+                if self.has_foresight(side) && committed == side.opponent() =>
+            {
+                Some(action)
+            }
+            _ => None,
+        }
+    }
+
+    // The action `action` stands for, popping the combo queue for Action::Combo.
+    fn take_combo(&mut self, side: Side, action: Action) -> Action {
+        match action {
+            Action::Combo => self.wizard_mut(side).queue.pop_front().unwrap_or(action),
+            _ => action,
+        }
+    }
+
+    // `action`, unless a Compel forces the wizard on `side` to repeat a move it can still play.
+    fn compelled_action(&self, side: Side, action: Action) -> Action {
+        match self.wizard(side).compelled {
+            Some(forced) if self.can_cast(side, forced) => forced,
+            _ => action,
+        }
+    }
+
+    pub fn tick(&mut self, leftaction: Action, rightaction: Action) -> Result<(), GameError> {
+        let leftaction = self.compelled_action(Side::Left, leftaction);
+        let rightaction = self.compelled_action(Side::Right, rightaction);
+        // Filters illegal moves
+        self.check_legal(Side::Left, leftaction)?;
+        self.check_legal(Side::Right, rightaction)?;
+        // Checked up front so a failed tick leaves the game untouched.
+        let next_turn = self
+            .turn_count
+            .checked_add(1)
+            .ok_or(GameError::TurnOverflow)?;
+        let leftaction = self.take_combo(Side::Left, leftaction);
+        let rightaction = self.take_combo(Side::Right, rightaction);
+        self.left_wizard.compelled = None;
+        self.right_wizard.compelled = None;
+        self.events.clear();
+        self.emit(GameEvent::TurnStarted { turn: next_turn });
+        if leftaction == Action::Surrender || rightaction == Action::Surrender {
+            // Surrendering ends the game on the spot, the other action never resolves.
+            self.left_wizard.surrendered = leftaction == Action::Surrender;
+            self.right_wizard.surrendered = rightaction == Action::Surrender;
+        } else {
+            self.resolve(leftaction, rightaction);
+        }
+        // Whatever followed an Empower used the buff up, or wasted it.
+        self.left_wizard.empowered = leftaction == Action::Empower;
+        self.right_wizard.empowered = rightaction == Action::Empower;
+        self.left_wizard.last_action = Some(leftaction);
+        self.right_wizard.last_action = Some(rightaction);
+        self.turn_count = next_turn;
+        self.history.push((leftaction, rightaction));
+        self.committed = None;
+        self.check_bounds();
+        self.update_result();
+        if let Some(result) = self.result() {
+            self.emit(GameEvent::GameEnded {
+                winner: result.winner,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh game with `turns` turns already on the clock, more than ticking could get to.
+    fn after_turns(turns: u32) -> Game {
+        let mut game = Game::new();
+        game.turn_count = turns;
+        game
+    }
+
+    // Both wizards on 20 HP and 5 mana, so every branch of `evaluate` is affordable.
+    fn even_position() -> Game {
+        let mut game = Game::new();
+        for wizard in [&mut game.left_wizard, &mut game.right_wizard] {
+            wizard.health = 20;
+            wizard.mana = 5;
+        }
+        game
+    }
+
+    #[test]
+    fn evaluate_branches_from_an_even_position() {
+        // (left, right, left (hp, mana), right (hp, mana)) after one turn from 20 HP and 5 mana each,
+        // passive mana included.
+        let cases = [
+            (Action::Fireball, Action::Concentrate, (20, 5), (17, 10)),
+            (Action::LightningBolt, Action::Reflect, (14, 4), (20, 4)),
+            (Action::Fireball, Action::ManaShield, (20, 5), (20, 5)),
+            (Action::Strike, Action::Teleport, (20, 6), (20, 4)),
+            (Action::BloodMagic, Action::Strike, (15, 11), (20, 6)),
+            (Action::ManaBurn, Action::Concentrate, (20, 4), (20, 7)),
+            (Action::ManaBurn, Action::Reflect, (20, 1), (20, 4)),
+            (Action::ManaBurn, Action::Teleport, (20, 4), (20, 4)),
+            (Action::Release, Action::Concentrate, (20, 6), (20, 10)),
+            (Action::ManaShield, Action::Reflect, (20, 5), (20, 4)),
+        ];
+        for (left, right, left_after, right_after) in cases {
+            let mut game = even_position();
+            game.tick(left, right).unwrap();
+            let state = |side| {
+                let wizard = game.wizard(side);
+                (wizard.health(), wizard.mana())
+            };
+            assert_eq!(
+                (state(Side::Left), state(Side::Right)),
+                (left_after, right_after),
+                "{:?} vs {:?}",
+                left,
+                right
+            );
+        }
+    }
+
+    #[test]
+    fn evaluate_releases_stored_charges() {
+        let mut game = even_position();
+        game.tick(Action::Charge, Action::Strike).unwrap();
+        game.tick(Action::Charge, Action::Concentrate).unwrap();
+        assert_eq!(game.wizard(Side::Left).charges(), 2);
+        game.tick(Action::Release, Action::Concentrate).unwrap();
+        assert_eq!(game.wizard(Side::Left).charges(), 0);
+        assert_eq!(game.wizard(Side::Right).health(), 16);
+    }
+
+    #[test]
+    fn tick_refuses_to_overflow_the_turn_count() {
+        let mut game = after_turns(u32::MAX - 1);
+        assert_eq!(game.tick(Action::Concentrate, Action::Concentrate), Ok(()));
+        assert_eq!(game.turn_count(), u32::MAX);
+
+        let before = game.clone();
+        assert_eq!(
+            game.tick(Action::Concentrate, Action::Concentrate),
+            Err(GameError::TurnOverflow)
+        );
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    fn win_probability_is_cached_until_a_tick() {
+        let mut game = even_position();
+        game.win_probability_with(Side::Left, 50);
+        // Plant a different answer under the same key, a second ask in the same turn must return it.
+        let planted = (game.fingerprint(), game.turn_count, 50, 0.25);
+        *game.rollout_cache.0.lock().unwrap() = Some(planted);
+        assert_eq!(game.win_probability_with(Side::Left, 50), 0.25);
+        assert_eq!(game.win_probability_with(Side::Right, 50), 0.75);
+        // Another rollout count is a different question.
+        assert_ne!(game.win_probability_with(Side::Left, 60), 0.25);
+
+        game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+        let fresh = game.clone();
+        *fresh.rollout_cache.0.lock().unwrap() = None;
+        let after = game.win_probability_with(Side::Left, 50);
+        assert_ne!(after, 0.25);
+        assert_eq!(after, fresh.win_probability_with(Side::Left, 50));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{Action, ALL_ACTIONS};
+use std::fmt;
+
+// Languages the UI can show action names in.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Lang {
+    #[default]
+    English,
+    Spanish,
+    German,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 3] = [Lang::English, Lang::Spanish, Lang::German];
+
+    // From an ISO 639-1 code such as "es", anything unknown falls back to English.
+    pub fn from_code(code: &str) -> Lang {
+        match code.to_ascii_lowercase().as_str() {
+            "es" => Lang::Spanish,
+            "de" => Lang::German,
+            _ => Lang::English,
+        }
+    }
+}
+
+// Names in ALL_ACTIONS order, one table per language.
+const ENGLISH: [&str; ALL_ACTIONS.len()] = [
+    "Strike",
+    "Fireball",
+    "Lightning Bolt",
+    "Mana Shield",
+    "Reflect",
+    "Concentrate",
+    "Exchange",
+    "Mana Burn",
+    "Blood Magic",
+    "Charge",
+    "Release",
+    "Teleport",
+    "Foresight",
+    "Infuse",
+    "Compel",
+    "Empower",
+    "Combo",
+    "Surrender",
+];
+
+const SPANISH: [&str; ALL_ACTIONS.len()] = [
+    "Golpe",
+    "Bola de fuego",
+    "Relámpago",
+    "Escudo de maná",
+    "Reflejo",
+    "Concentración",
+    "Intercambio",
+    "Quemar maná",
+    "Magia de sangre",
+    "Carga",
+    "Descarga",
+    "Teletransporte",
+    "Clarividencia",
+    "Infusión",
+    "Coacción",
+    "Potenciar",
+    "Combo",
+    "Rendición",
+];
+
+const GERMAN: [&str; ALL_ACTIONS.len()] = [
+    "Schlag",
+    "Feuerball",
+    "Blitzschlag",
+    "Manaschild",
+    "Reflexion",
+    "Konzentration",
+    "Austausch",
+    "Manabrand",
+    "Blutmagie",
+    "Aufladen",
+    "Entladen",
+    "Teleport",
+    "Voraussicht",
+    "Infusion",
+    "Zwang",
+    "Ermächtigen",
+    "Kombo",
+    "Aufgeben",
+];
+
+impl Action {
+    pub fn localized_name(&self, lang: Lang) -> &'static str {
+        let table = match lang {
+            Lang::English => &ENGLISH,
+            Lang::Spanish => &SPANISH,
+            Lang::German => &GERMAN,
+        };
+        table[self.code() as usize]
+    }
+}
+
+// The English name, what every UI falls back to.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.localized_name(Lang::English))
+    }
+}
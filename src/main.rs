@@ -1,213 +1,174 @@
-use anyhow::{anyhow, Result};
-use rand::prelude::IndexedRandom;
-use std::cmp::PartialEq;
-/*
-Wizard duel
-Simultaneous
-Each wizard starts with 25 HP and 5 mana
-If HP reaches 0, the wizard dies, and they lose
-Mana increases by 1 every turn.
-*/
-
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum Action {
-    // Deals 2 damage, manaless.
-    Strike,
-    // Deals 3 damage, but costs 2 mana.
-    Fireball,
-    // Deals 5 damage, but costs 3 mana.
-    LightningBolt,
-    // Blocks all incoming damage, costs 1 mana.
-    ManaShield,
-    // If opponent does any attack, the attack is reflected and deals +1 damage. Costs 2 mana.
-    Reflect,
-    // Restores 4 mana (not including the passive gain).
-    Concentrate,
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::io::BufRead;
+use wizardfight_wasm::rng::GameRng;
+use wizardfight_wasm::schema::interface_schema;
+use wizardfight_wasm::simulation::run_matchup;
+use wizardfight_wasm::strategy::strategy_by_name;
+use wizardfight_wasm::{Action, Game, GameConfig, Side};
+
+// Value following `flag` on the command line, if it was given.
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
 }
 
-impl Action {
-    pub fn damage_amnt(&self) -> u8 {
-        match self {
-            Action::Strike => 2,
-            Action::Fireball => 3,
-            Action::LightningBolt => 5,
-            Action::ManaShield => 0,
-            Action::Reflect => 0,
-            Action::Concentrate => 0,
-        }
+// Score swing of every action pairing over one turn from a neutral start
+// where both wizards can afford anything, Left's actions down the side.
+fn print_action_matrix() {
+    let config = GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    };
+    let game = Game::with_config(config).expect("starting_mana 5 is valid");
+    let name = |action| format!("{:?}", action).chars().take(7).collect::<String>();
+    let header: Vec<String> = Game::matrix_actions()
+        .iter()
+        .map(|&action| format!("{:>8}", name(action)))
+        .collect();
+    println!("{:8}{}", "", header.join(""));
+    for (&action, row) in Game::matrix_actions().iter().zip(game.action_matrix()) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|swing| match swing {
+                Some(swing) => format!("{:>8}", swing),
+                None => format!("{:>8}", "-"),
+            })
+            .collect();
+        println!("{:8}{}", name(action), cells.join(""));
     }
-
-    pub fn mana_cost(&self) -> i8 {
-        match self {
-            Action::Strike => 0,
-            Action::Fireball => 1,
-            Action::LightningBolt => 2,
-            Action::ManaShield => 1,
-            Action::Reflect => 2,
-            Action::Concentrate => -4,
-        }
+    for dominated in game.dominated_actions() {
+        println!("dominated: {}", dominated.explanation);
     }
 }
 
-#[derive(PartialEq, Debug)]
-enum Side {
-    Left,
-    Right,
-    Neither,
-}
-
-struct Wizard {
-    health: u8,
-    mana: u8,
-}
-
-impl Wizard {
-    fn new() -> Wizard {
-        Wizard {
-            health: 25,
-            mana: 1,
-        }
+// Every first-turn pairing from the default start, best for Left first.
+fn print_opening_book() {
+    let mut book = Game::new().opening_book();
+    book.sort_by_key(|opening| std::cmp::Reverse(opening.score));
+    for opening in book {
+        println!(
+            "{:?} vs {:?}: {} ({} HP vs {} HP)",
+            opening.left,
+            opening.right,
+            opening.score,
+            opening.position.left.health,
+            opening.position.right.health
+        );
     }
 }
 
-struct Game {
-    left_wizard: Wizard,
-    right_wizard: Wizard,
-    turn_count: u32,
-}
-
-impl Game {
-    pub fn new() -> Game {
-        Game {
-            left_wizard: Wizard::new(),
-            right_wizard: Wizard::new(),
-            turn_count: 0,
-        }
-    }
-
-    fn damage_wizard(&mut self, side: Side, damage: u8) {
-        if side == Side::Left {
-            self.left_wizard.health = self.left_wizard.health.saturating_sub(damage);
-        } else if side == Side::Right {
-            self.right_wizard.health = self.right_wizard.health.saturating_sub(damage);
-        }
-    }
-    // expects a negative "mana cost"
-    fn add_mana(&mut self, side: Side, mana: i8) {
-        let mana = (1. / mana as f64) as u8; // stupid hack to flip the sign
-        if side == Side::Left {
-            self.left_wizard.mana = self.left_wizard.mana.saturating_add(mana);
-        } else if side == Side::Right {
-            self.right_wizard.mana = self.right_wizard.mana.saturating_add(mana);
-        }
-    }
-
-    fn remove_mana(&mut self, side: Side, mana_cost: u8) {
-        if side == Side::Left {
-            self.left_wizard.mana = self.left_wizard.mana.saturating_sub(mana_cost);
-        } else if side == Side::Right {
-            self.right_wizard.mana = self.right_wizard.mana.saturating_sub(mana_cost);
-        }
-    }
-
-    fn game_completed(&self) -> (bool, Side) {
-        if (self.left_wizard.health == 0) && (self.right_wizard.health == 0) {
-            return (true, Side::Neither);
-        }
-
-        if self.left_wizard.health == 0 {
-            return (true, Side::Right);
-        }
-
-        if self.right_wizard.health == 0 {
-            return (true, Side::Left);
-        }
-        (false, Side::Neither)
-    }
-
-    fn evaluate(&mut self, attacker_side: Side, attacker: Action, defender: Action) {
-        let defender_side = if attacker_side == Side::Left {
-            Side::Right
-        } else {
-            Side::Left
+/*
+Plays Left from stdin against the `opponent` strategy, one action name or alias per line.
+With `hints` on, a move some other action beats against every reply gets a
+gentle note before the turn is played.
+*/
+fn play_interactive(opponent: &str, hints: bool, seed: u64) {
+    let Some(mut opponent) = strategy_by_name(opponent) else {
+        eprintln!(
+            "unknown strategy {} (pick from random, adaptive, greedy, minimax, pressure)",
+            opponent
+        );
+        std::process::exit(1);
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new();
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    while game.result().is_none() {
+        let (you, them) = (game.wizard(Side::Left), game.wizard(Side::Right));
+        println!(
+            "turn {}: you {} HP {} mana, opponent {} HP {} mana",
+            game.turn_count(),
+            you.health(),
+            you.mana(),
+            them.health(),
+            them.mana()
+        );
+        // With foresight the opponent has to commit before you pick.
+        if game.has_foresight(Side::Left) && game.foresight(Side::Left).is_none() {
+            let reply = opponent.choose(&game, Side::Right, &mut rng);
+            game.commit(Side::Right, reply);
+            println!("foresight: the opponent will play {:?}", reply);
+        }
+        let Some(Ok(line)) = lines.next() else {
+            return;
         };
-        match attacker {
-            Action::Strike | Action::Fireball | Action::LightningBolt => {
-                if defender == Action::Reflect {
-                    self.damage_wizard(attacker_side, attacker.damage_amnt());
-                } else if defender == Action::ManaShield {
-                    ()
-                } else {
-                    self.damage_wizard(defender_side, attacker.damage_amnt());
-                }
+        let action: Action = match line.parse() {
+            Ok(action) => action,
+            Err(err) => {
+                println!("{}", err);
+                continue;
             }
-            Action::Concentrate => self.add_mana(attacker_side, attacker.mana_cost()),
-            _ => (),
-        }
-    }
-
-    pub fn tick(&mut self, leftaction: Action, rightaction: Action) -> Result<()> {
-        // Filters illegal moves
-        match leftaction {
-            Action::Fireball | Action::LightningBolt | Action::ManaShield | Action::Reflect => {
-                if self.left_wizard.mana < leftaction.mana_cost() as u8 {
-                    return Err(anyhow!("Left wizard tried to do an illegal move."));
-                }
+        };
+        if hints {
+            if let Some(hint) = game.hint(Side::Left, action) {
+                println!("hint: {}", hint);
             }
-            _ => (),
         }
-        match rightaction {
-            Action::Fireball | Action::LightningBolt | Action::ManaShield | Action::Reflect => {
-                if self.right_wizard.mana < rightaction.mana_cost() as u8 {
-                    return Err(anyhow!("Right wizard did not have enough mana."));
-                }
+        let reply = match game.foresight(Side::Left) {
+            Some(reply) => reply,
+            None => {
+                game.commit(Side::Left, action);
+                opponent.choose(&game, Side::Right, &mut rng)
             }
-            _ => (),
-        }
-        if leftaction != Action::Concentrate {
-            self.remove_mana(Side::Left, leftaction.mana_cost() as u8);
-        }
-        if rightaction != Action::Concentrate {
-            self.remove_mana(Side::Right, rightaction.mana_cost() as u8);
+        };
+        match game.tick(action, reply) {
+            Ok(()) => println!("you played {:?}, the opponent played {:?}", action, reply),
+            Err(err) => println!("{}", err),
         }
-        self.evaluate(Side::Left, leftaction, rightaction);
-        self.evaluate(Side::Right, rightaction, leftaction);
-        self.add_mana(Side::Left, -1);
-        self.add_mana(Side::Right, -1);
-        self.turn_count += 1;
-        Ok(())
     }
+    println!("{:?}", game.status());
 }
 
 fn main() {
-    let mut leftwizard_wins = 0;
-    let mut rightwizard_wins = 0;
-    let mut ties = 0;
-    for _ in 0..1_000_000 {
-        let mut game = Game::new();
-        while !game.game_completed().0 {
-            let actions = vec![
-                Action::ManaShield,
-                Action::Reflect,
-                Action::Concentrate,
-                Action::Fireball,
-                Action::Strike,
-                Action::LightningBolt,
-            ];
-            let player1 = actions.choose(&mut rand::rng()).unwrap();
-            let player2 = actions.choose(&mut rand::rng()).unwrap();
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--matrix") {
+        print_action_matrix();
+        return;
+    }
+    if args.iter().any(|arg| arg == "--openings") {
+        print_opening_book();
+        return;
+    }
+    if args.iter().any(|arg| arg == "--schema") {
+        println!("{:#}", interface_schema());
+        return;
+    }
+    let left = arg_value(&args, "--left").unwrap_or_else(|| "random".to_string());
+    let right = arg_value(&args, "--right").unwrap_or_else(|| "random".to_string());
+    let games = arg_value(&args, "--games")
+        .map(|games| games.parse().expect("--games takes an unsigned integer"))
+        .unwrap_or(1_000_000);
+    // `--seed <n>` replays an earlier run, otherwise a fresh seed is picked.
+    let seed = arg_value(&args, "--seed")
+        .map(|seed| seed.parse().expect("--seed takes an unsigned integer"))
+        .unwrap_or_else(|| GameRng::from_entropy().next_u64());
+
+    if args.iter().any(|arg| arg == "--play") {
+        play_interactive(&right, args.iter().any(|arg| arg == "--hints"), seed);
+        return;
+    }
 
-            let _ = game.tick(player1.clone(), player2.clone());
-        }
-        match game.game_completed().1 {
-            Side::Left => leftwizard_wins += 1,
-            Side::Right => rightwizard_wins += 1,
-            Side::Neither => ties += 1,
+    let config = GameConfig::default();
+    // Printed up front so a surprising result can be reproduced with `--seed`.
+    println!("seed: {}, config: {:016x}", seed, config.config_hash());
+
+    let report = match run_matchup(&config, &left, &right, games, seed) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!(
+                "{} (pick from random, adaptive, greedy, minimax, pressure)",
+                err
+            );
+            std::process::exit(1);
         }
-    }
+    };
 
     println!(
-        "L: {}, R: {}, T: {}",
-        leftwizard_wins, rightwizard_wins, ties
+        "{} vs {}: L: {}, R: {}, T: {}",
+        left, right, report.left_wins, report.right_wins, report.draws
     );
 }
@@ -0,0 +1,128 @@
+use crate::{Action, Lang, ALL_ACTIONS};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseActionError {
+    // Neither an action name nor a registered alias.
+    Unknown(String),
+    // `alias` already stands for `action`, as its name or another alias.
+    Taken { alias: String, action: Action },
+}
+
+impl fmt::Display for ParseActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseActionError::Unknown(input) => write!(f, "unknown action {}", input),
+            ParseActionError::Taken { alias, action } => {
+                write!(f, "{} already means {:?}", alias, action)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseActionError {}
+
+// Shorthand every parser starts with.
+const BUILT_IN: [(&str, Action); 10] = [
+    ("lb", Action::LightningBolt),
+    ("bolt", Action::LightningBolt),
+    ("fb", Action::Fireball),
+    ("shield", Action::ManaShield),
+    ("conc", Action::Concentrate),
+    ("burn", Action::ManaBurn),
+    ("blood", Action::BloodMagic),
+    ("tp", Action::Teleport),
+    ("ff", Action::Surrender),
+    ("gg", Action::Surrender),
+];
+
+/*
+Reads actions typed by a player: the variant names, the names shown in every
+UI language ("Lightning Bolt", "Relámpago") and any registered aliases, all
+ignoring case and whitespace. An alias can never shadow an action name or be
+registered for two different actions, so every input means at most one action.
+*/
+#[derive(Clone, Debug)]
+pub struct ActionParser {
+    aliases: HashMap<String, Action>,
+}
+
+impl Default for ActionParser {
+    fn default() -> Self {
+        let mut parser = ActionParser {
+            aliases: HashMap::new(),
+        };
+        for (alias, action) in BUILT_IN {
+            parser
+                .add_alias(alias, action)
+                .expect("built-in aliases don't collide");
+        }
+        parser
+    }
+}
+
+fn normalize(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Every name `action` is known by, before normalizing.
+fn names(action: Action) -> impl Iterator<Item = String> {
+    let localized = Lang::ALL.map(|lang| action.localized_name(lang).to_string());
+    std::iter::once(format!("{:?}", action)).chain(localized)
+}
+
+impl ActionParser {
+    // A parser knowing only the action names, without the built-in aliases.
+    pub fn names_only() -> ActionParser {
+        ActionParser {
+            aliases: HashMap::new(),
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<Action> {
+        ALL_ACTIONS
+            .iter()
+            .copied()
+            .find(|&action| names(action).any(|name| normalize(&name) == key))
+            .or_else(|| self.aliases.get(key).copied())
+    }
+
+    // Registering an alias again for the action it already means is a no-op.
+    pub fn add_alias(&mut self, alias: &str, action: Action) -> Result<(), ParseActionError> {
+        let key = normalize(alias);
+        match self.lookup(&key) {
+            Some(existing) if existing != action => Err(ParseActionError::Taken {
+                alias: key,
+                action: existing,
+            }),
+            _ => {
+                self.aliases.insert(key, action);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Action, ParseActionError> {
+        let key = normalize(input);
+        self.lookup(&key)
+            .ok_or_else(|| ParseActionError::Unknown(input.trim().to_string()))
+    }
+}
+
+// Action names and the built-in aliases, see `ActionParser`.
+impl FromStr for Action {
+    type Err = ParseActionError;
+
+    // Parses with the built-in aliases, the parser built once on first use.
+    fn from_str(input: &str) -> Result<Action, ParseActionError> {
+        static PARSER: OnceLock<ActionParser> = OnceLock::new();
+        PARSER.get_or_init(ActionParser::default).parse(input)
+    }
+}
@@ -0,0 +1,45 @@
+use crate::tournament::TournamentResult;
+use std::collections::HashMap;
+
+const STARTING_RATING: f64 = 1500.0;
+const K_FACTOR: f64 = 32.0;
+// Aggregated match scores are replayed this many times so the ratings settle
+// instead of depending on the order the matches were played in.
+const PASSES: usize = 200;
+
+fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/*
+ELO ratings for every strategy in a tournament.
+Each match counts as one rated game scored by its win rate (draws count half),
+so the result is deterministic for a given TournamentResult.
+*/
+pub fn compute_ratings(results: &TournamentResult) -> HashMap<String, f64> {
+    let mut ratings: HashMap<String, f64> = HashMap::new();
+    for record in &results.matches {
+        ratings
+            .entry(record.left.clone())
+            .or_insert(STARTING_RATING);
+        ratings
+            .entry(record.right.clone())
+            .or_insert(STARTING_RATING);
+    }
+
+    for _ in 0..PASSES {
+        for record in &results.matches {
+            let games = record.games();
+            if games == 0 {
+                continue;
+            }
+            let left_score = (record.left_wins as f64 + record.draws as f64 / 2.0) / games as f64;
+            let left_rating = ratings[&record.left];
+            let right_rating = ratings[&record.right];
+            let delta = K_FACTOR * (left_score - expected_score(left_rating, right_rating));
+            *ratings.get_mut(&record.left).unwrap() += delta;
+            *ratings.get_mut(&record.right).unwrap() -= delta;
+        }
+    }
+    ratings
+}
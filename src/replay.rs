@@ -0,0 +1,408 @@
+use crate::strategy::{RandomStrategy, Strategy};
+use crate::{
+    Action, EffectKind, Game, GameConfig, GameError, GameResult, Side, Wizard, ALL_ACTIONS,
+    MAX_REJECTED_PICKS,
+};
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WizardState {
+    pub health: u8,
+    pub mana: u8,
+}
+
+impl From<&Wizard> for WizardState {
+    fn from(wizard: &Wizard) -> Self {
+        WizardState {
+            health: wizard.health(),
+            mana: wizard.mana(),
+        }
+    }
+}
+
+// Both actions of a turn and the state they left the wizards in.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TurnOutcome {
+    pub left_action: Action,
+    pub right_action: Action,
+    pub left: WizardState,
+    pub right: WizardState,
+}
+
+// One wizard as a frame draws it: the HP bar, mana and whatever should be overlaid.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FrameWizard {
+    pub health: u8,
+    pub max_health: u8,
+    pub mana: u8,
+    pub last_action: Option<Action>,
+    pub charges: u8,
+    pub effects: Vec<EffectKind>,
+}
+
+// A renderable still of the game, see `Replay::frames`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FrameState {
+    // Turns played so far, 0 for the starting position.
+    pub turn: u32,
+    pub left: FrameWizard,
+    pub right: FrameWizard,
+}
+
+impl FrameState {
+    fn of(game: &Game) -> FrameState {
+        let wizard = |side| {
+            let wizard: &Wizard = game.wizard(side);
+            FrameWizard {
+                health: wizard.health(),
+                max_health: game.config().starting_health,
+                mana: wizard.mana(),
+                last_action: wizard.last_action(),
+                charges: wizard.charges(),
+                effects: wizard.effects().iter().map(|effect| effect.kind).collect(),
+            }
+        };
+        FrameState {
+            turn: game.turn_count(),
+            left: wizard(Side::Left),
+            right: wizard(Side::Right),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub config: GameConfig,
+    // Seed the game was created with, see `Game::with_seed`.
+    pub seed: u64,
+    pub turns: Vec<TurnOutcome>,
+}
+
+impl Replay {
+    pub fn new(config: GameConfig) -> Replay {
+        Replay::with_seed(config, 0)
+    }
+
+    pub fn with_seed(config: GameConfig, seed: u64) -> Replay {
+        Replay {
+            config,
+            seed,
+            turns: Vec::new(),
+        }
+    }
+
+    // Records a turn that was just played, `after` being the game once it resolved.
+    pub fn record(&mut self, left_action: Action, right_action: Action, after: &Game) {
+        self.turns.push(TurnOutcome {
+            left_action,
+            right_action,
+            left: after.wizard(Side::Left).into(),
+            right: after.wizard(Side::Right).into(),
+        });
+    }
+
+    /*
+    Replays the recorded actions through a fresh game and checks every turn ends
+    in the recorded state. Fails on the first turn (counting from 1) that doesn't,
+    which is how old replays get caught after a mechanics change.
+    */
+    pub fn verify(&self) -> Result<()> {
+        let mut game = Game::with_seed(self.config.clone(), self.seed)?;
+        for (index, recorded) in self.turns.iter().enumerate() {
+            let turn = index + 1;
+            game.tick(recorded.left_action, recorded.right_action)
+                .map_err(|err| anyhow!("turn {} could not be replayed: {}", turn, err))?;
+            let left = WizardState::from(game.wizard(Side::Left));
+            let right = WizardState::from(game.wizard(Side::Right));
+            if left != recorded.left || right != recorded.right {
+                return Err(anyhow!(
+                    "turn {} diverged: recorded {:?} / {:?}, replayed {:?} / {:?}",
+                    turn,
+                    recorded.left,
+                    recorded.right,
+                    left,
+                    right
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /*
+    Replays the game into one frame for the start and one per turn, for front
+    ends that animate a replay into a GIF or video. Data only, drawing is up
+    to the caller. Fails like `verify` when a recorded turn can't be played.
+    */
+    pub fn frames(&self) -> Result<Vec<FrameState>> {
+        let mut game = Game::with_seed(self.config.clone(), self.seed)?;
+        let mut frames = vec![FrameState::of(&game)];
+        for (index, recorded) in self.turns.iter().enumerate() {
+            game.tick(recorded.left_action, recorded.right_action)
+                .map_err(|err| anyhow!("turn {} could not be replayed: {}", index + 1, err))?;
+            frames.push(FrameState::of(&game));
+        }
+        Ok(frames)
+    }
+}
+
+// Bumped whenever the layout or the action codes change.
+const COMPRESSED_VERSION: u8 = 2;
+// Version, config hash, seed and turn count.
+const COMPRESSED_HEADER: usize = 1 + 8 + 8 + 4;
+// Bits for one turn's (left, right) pair of action codes.
+const PAIR_BITS: u32 = (ALL_ACTIONS.len() * ALL_ACTIONS.len() - 1).ilog2() + 1;
+
+impl Replay {
+    /*
+    Packs the replay for bulk storage: a header, then each turn's two action
+    codes as one number, PAIR_BITS bits apiece. The config itself isn't
+    stored, only its hash, and the wizard states are left for `decompress`
+    to replay.
+    */
+    pub fn compress(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            COMPRESSED_HEADER + (self.turns.len() * PAIR_BITS as usize).div_ceil(8),
+        );
+        bytes.push(COMPRESSED_VERSION);
+        bytes.extend(self.config.config_hash().to_le_bytes());
+        bytes.extend(self.seed.to_le_bytes());
+        bytes.extend((self.turns.len() as u32).to_le_bytes());
+        let (mut buffer, mut buffered) = (0u32, 0);
+        for turn in &self.turns {
+            let pair = turn.left_action.code() as u32 * ALL_ACTIONS.len() as u32
+                + turn.right_action.code() as u32;
+            buffer |= pair << buffered;
+            buffered += PAIR_BITS;
+            while buffered >= 8 {
+                bytes.push(buffer as u8);
+                buffer >>= 8;
+                buffered -= 8;
+            }
+        }
+        if buffered > 0 {
+            bytes.push(buffer as u8);
+        }
+        bytes
+    }
+
+    /*
+    Rebuilds a replay from `compress` by playing its turns under `config`,
+    which has to be the config it was recorded with. Fails on a config or
+    version mismatch, truncated input, or a turn that can't be played.
+    */
+    pub fn decompress(bytes: &[u8], config: GameConfig) -> Result<Replay> {
+        if bytes.len() < COMPRESSED_HEADER {
+            return Err(anyhow!("compressed replay is missing its header"));
+        }
+        if bytes[0] != COMPRESSED_VERSION {
+            return Err(anyhow!("unknown compressed replay version {}", bytes[0]));
+        }
+        let config_hash = u64::from_le_bytes(bytes[1..9].try_into()?);
+        if config_hash != config.config_hash() {
+            return Err(anyhow!(
+                "replay was recorded with config {:016x}, not {:016x}",
+                config_hash,
+                config.config_hash()
+            ));
+        }
+        let seed = u64::from_le_bytes(bytes[9..17].try_into()?);
+        let turns = u32::from_le_bytes(bytes[17..21].try_into()?) as usize;
+        let packed = &bytes[COMPRESSED_HEADER..];
+        let needed = turns
+            .checked_mul(PAIR_BITS as usize)
+            .map(|bits| bits.div_ceil(8));
+        if needed.is_none_or(|needed| packed.len() < needed) {
+            return Err(anyhow!("compressed replay is cut short"));
+        }
+
+        let mut game = Game::with_seed(config.clone(), seed)?;
+        let mut replay = Replay::with_seed(config, seed);
+        let mut packed = packed.iter();
+        let (mut buffer, mut buffered) = (0u32, 0);
+        for turn in 1..=turns {
+            while buffered < PAIR_BITS {
+                buffer |= (*packed.next().expect("length checked above") as u32) << buffered;
+                buffered += 8;
+            }
+            let pair = buffer & ((1 << PAIR_BITS) - 1);
+            buffer >>= PAIR_BITS;
+            buffered -= PAIR_BITS;
+            let action = |code: u32| {
+                u8::try_from(code)
+                    .ok()
+                    .and_then(Action::from_code)
+                    .ok_or_else(|| anyhow!("turn {} has unknown action code {}", turn, code))
+            };
+            let count = ALL_ACTIONS.len() as u32;
+            let (left, right) = (action(pair / count)?, action(pair % count)?);
+            game.tick(left, right)
+                .map_err(|err| anyhow!("turn {} could not be replayed: {}", turn, err))?;
+            replay.record(left, right, &game);
+        }
+        Ok(replay)
+    }
+}
+
+/*
+How exciting a recorded game was, for picking highlights out of big runs.
+Rewards a close final HP gap, every change of who's ahead on HP, a winner
+who came back from low HP, and a finishing blow dealt through Reflect.
+Only depends on the replay, so the same game always scores the same.
+*/
+pub fn excitement(replay: &Replay) -> f64 {
+    let Some(last) = replay.turns.last() else {
+        return 0.0;
+    };
+    let starting_health = replay.config.starting_health.max(1) as f64;
+    let mut score = 0.0;
+
+    let gap = last.left.health.abs_diff(last.right.health) as f64;
+    score += 10.0 * (1.0 - gap / starting_health);
+
+    let mut leader = None;
+    for turn in &replay.turns {
+        let ahead = match turn.left.health.cmp(&turn.right.health) {
+            Ordering::Greater => Some(Side::Left),
+            Ordering::Less => Some(Side::Right),
+            Ordering::Equal => continue,
+        };
+        if leader.is_some() && leader != ahead {
+            score += 5.0;
+        }
+        leader = ahead;
+    }
+
+    let winner = match (last.left.health, last.right.health) {
+        (0, 0) => None,
+        (0, _) => Some(Side::Right),
+        (_, 0) => Some(Side::Left),
+        _ => None,
+    };
+    if let Some(winner) = winner {
+        let lowest = replay
+            .turns
+            .iter()
+            .map(|turn| match winner {
+                Side::Left => turn.left.health,
+                _ => turn.right.health,
+            })
+            .min()
+            .unwrap_or(0) as f64;
+        score += 10.0 * (1.0 - lowest / starting_health);
+
+        let (winning, losing) = match winner {
+            Side::Left => (last.left_action, last.right_action),
+            _ => (last.right_action, last.left_action),
+        };
+        if winning == Action::Reflect && losing.is_offensive() {
+            score += 5.0;
+        }
+    }
+    score
+}
+
+// Indices of the `k` most exciting replays, most exciting first.
+pub fn most_exciting(replays: &[Replay], k: usize) -> Vec<usize> {
+    let mut scored: Vec<(usize, f64)> = replays
+        .iter()
+        .enumerate()
+        .map(|(index, replay)| (index, excitement(replay)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.into_iter().take(k).map(|(index, _)| index).collect()
+}
+
+// Everything `play_game` produces: how the game ended and every turn it took to get there.
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub result: GameResult,
+    pub replay: Replay,
+}
+
+/*
+Plays a whole game between two strategies and returns its result and replay.
+The game and both strategies' RNGs are seeded from `seed`, so the same
+arguments always play the same game. Illegal picks are asked again, and
+`config.max_turns` should be set for strategies that can stall each other.
+*/
+pub fn play_game(
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    config: GameConfig,
+    seed: u64,
+) -> Result<GameRecord, GameError> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut left_rng = StdRng::seed_from_u64(rng.random());
+    let mut right_rng = StdRng::seed_from_u64(rng.random());
+    let mut game = Game::with_seed(config.clone(), seed)?;
+    let mut replay = Replay::with_seed(config, seed);
+    let result = play_out(
+        &mut game,
+        left,
+        right,
+        &mut left_rng,
+        &mut right_rng,
+        |game| {
+            // The history holds what was actually played, combos included.
+            let &(left_action, right_action) = game.history().last().expect("turn was played");
+            replay.record(left_action, right_action, game);
+        },
+    )?;
+    Ok(GameRecord { result, replay })
+}
+
+/*
+Plays `game` to the end through `step_with`, calling `played` after every
+turn, and returns the result. Every module that runs strategies against each
+other goes through here. Illegal picks are asked again, up to
+MAX_REJECTED_PICKS in a row, and then the last rejection is returned so a
+strategy stuck on a move `tick` refuses can't hang the caller.
+*/
+pub(crate) fn play_out(
+    game: &mut Game,
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    left_rng: &mut dyn RngCore,
+    right_rng: &mut dyn RngCore,
+    mut played: impl FnMut(&Game),
+) -> Result<GameResult, GameError> {
+    let mut rejected = 0;
+    while game.result().is_none() {
+        match game.step_with(left, right, left_rng, right_rng) {
+            Ok(_) => {
+                rejected = 0;
+                played(game);
+            }
+            Err(GameError::TurnOverflow) => return Err(GameError::TurnOverflow),
+            Err(err) => {
+                rejected += 1;
+                if rejected >= MAX_REJECTED_PICKS {
+                    return Err(err);
+                }
+            }
+        }
+    }
+    Ok(game.result().expect("loop only ends with a result"))
+}
+
+/*
+First seed from 0 up whose `play_game` between two RandomStrategy wizards
+on the default config gives a replay `predicate` accepts, trying at most
+`max_attempts` seeds. Handy for digging up a game that shows off a mechanic
+for docs or tests, replayable afterwards with the same seed.
+*/
+pub fn find_seed(predicate: impl Fn(&Replay) -> bool, max_attempts: u64) -> Option<u64> {
+    (0..max_attempts).find(|&seed| {
+        play_game(
+            &mut RandomStrategy,
+            &mut RandomStrategy,
+            GameConfig::default(),
+            seed,
+        )
+        .is_ok_and(|record| predicate(&record.replay))
+    })
+}
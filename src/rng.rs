@@ -0,0 +1,35 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/*
+The randomness hosts hand to strategies, simulations and `step_with`.
+Nothing in the crate pulls from a global RNG, everything takes a
+`&mut dyn RngCore`, so a GameRng plugs in anywhere. Seeded runs replay
+exactly, `from_entropy` seeds from the OS through `getrandom`, which is the
+browser's crypto API on wasm32 through its `wasm_js` backend.
+*/
+pub struct GameRng(StdRng);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> GameRng {
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+
+    pub fn from_entropy() -> GameRng {
+        GameRng(StdRng::from_os_rng())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0.fill_bytes(dst)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{stable_hash, Action, Game, GameStatus, Side};
+use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Mutex;
+
+// Playouts `win_probability` runs.
+const DEFAULT_ROLLOUTS: u32 = 200;
+// A playout still going after this many turns goes to the healthier wizard.
+const ROLLOUT_TURNS: u32 = 100;
+
+/*
+Last `win_probability_with` answer for Left, keyed on the position's fingerprint,
+the turn count and the rollout count. Behind a Mutex so a Game can still be
+shared across threads. Only ever a shortcut, so it never makes two games unequal.
+*/
+#[derive(Debug, Default)]
+pub(crate) struct RolloutCache(pub(crate) Mutex<Option<(u64, u32, u32, f64)>>);
+
+impl RolloutCache {
+    fn get(&self, key: (u64, u32, u32)) -> Option<f64> {
+        match *self.0.lock().ok()? {
+            Some((fingerprint, turn, rollouts, left)) if (fingerprint, turn, rollouts) == key => {
+                Some(left)
+            }
+            _ => None,
+        }
+    }
+
+    fn set(&self, (fingerprint, turn, rollouts): (u64, u32, u32), left: f64) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = Some((fingerprint, turn, rollouts, left));
+        }
+    }
+}
+
+impl Clone for RolloutCache {
+    fn clone(&self) -> Self {
+        let cached = self.0.lock().ok().and_then(|cached| *cached);
+        RolloutCache(Mutex::new(cached))
+    }
+}
+
+impl PartialEq for RolloutCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for RolloutCache {}
+
+impl Game {
+    // `win_probability_with` DEFAULT_ROLLOUTS playouts, for a spectator win bar.
+    pub fn win_probability(&self, side: Side) -> f64 {
+        self.win_probability_with(side, DEFAULT_ROLLOUTS)
+    }
+
+    /*
+    Share of `rollouts` random playouts from here that `side` wins, draws
+    counting half. Both wizards pick uniformly among their legal actions,
+    never surrendering. The playouts are seeded from the position, so the
+    same position always gets the same estimate, and the last answer is
+    kept until the position changes.
+    */
+    pub fn win_probability_with(&self, side: Side, rollouts: u32) -> f64 {
+        let key = (self.fingerprint(), self.turn_count, rollouts);
+        let left = self.rollout_cache.get(key).unwrap_or_else(|| {
+            let mut rng = StdRng::seed_from_u64(stable_hash(&(key.0, "rollouts")));
+            let score: f64 = (0..rollouts).map(|_| self.rollout(&mut rng)).sum();
+            let left = score / rollouts.max(1) as f64;
+            self.rollout_cache.set(key, left);
+            left
+        });
+        match side {
+            Side::Left => left,
+            Side::Right => 1.0 - left,
+            Side::Neither => panic!("Side::Neither can't win"),
+        }
+    }
+
+    // One random playout, 1 for a Left win, 0 for a Right win and 0.5 otherwise.
+    fn rollout(&self, rng: &mut StdRng) -> f64 {
+        let mut game = self.clone();
+        let pick = |game: &Game, side: Side, rng: &mut StdRng| {
+            let legal: Vec<Action> = game
+                .available_actions(side)
+                .into_iter()
+                .filter(|&action| action != Action::Surrender)
+                .collect();
+            *legal.choose(rng).unwrap_or(&Action::Concentrate)
+        };
+        for _ in 0..ROLLOUT_TURNS {
+            if game.result().is_some() {
+                break;
+            }
+            let left = pick(&game, Side::Left, rng);
+            let right = pick(&game, Side::Right, rng);
+            if game.tick(left, right).is_err() {
+                break;
+            }
+        }
+        let health = |side| game.wizard(side).health();
+        match game.status() {
+            GameStatus::Winner(Side::Left) => 1.0,
+            GameStatus::Winner(_) => 0.0,
+            GameStatus::Draw => 0.5,
+            GameStatus::Ongoing => match health(Side::Left).cmp(&health(Side::Right)) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Less => 0.0,
+                std::cmp::Ordering::Equal => 0.5,
+            },
+        }
+    }
+}
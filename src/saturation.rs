@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/*
+How often HP and mana arithmetic was clamped instead of carried out in full.
+Clamping is what the rules ask for, but a count far off from what a config
+should produce points at the overflow and sign bugs clamping hides.
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct SaturationStats {
+    // Damage beyond the HP the wizard had left.
+    pub health_floor: u32,
+    // Mana removed beyond what the wizard had, by a ManaBurn or a partial cast.
+    pub mana_floor: u32,
+    // Mana gains cut short by `max_mana`.
+    pub mana_cap: u32,
+}
+
+impl SaturationStats {
+    // Adds `other`'s counts to these, for totals over many games.
+    pub fn merge(&mut self, other: &SaturationStats) {
+        self.health_floor += other.health_floor;
+        self.mana_floor += other.mana_floor;
+        self.mana_cap += other.mana_cap;
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{Game, GameStatus, Side, WinReason, ALL_ACTIONS};
+use serde_json::{json, Map, Value};
+
+// Matched exhaustively, so a new side, status or reason won't build until it's named
+// here, the reminder to add it to that type's ALL as well.
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Left => "Left",
+        Side::Right => "Right",
+        Side::Neither => "Neither",
+    }
+}
+
+fn status_name(status: GameStatus) -> &'static str {
+    match status {
+        GameStatus::Ongoing => "Ongoing",
+        GameStatus::Winner(_) => "Winner",
+        GameStatus::Draw => "Draw",
+    }
+}
+
+fn reason_name(reason: WinReason) -> &'static str {
+    match reason {
+        WinReason::Lethal => "Lethal",
+        WinReason::TurnLimit => "TurnLimit",
+        WinReason::Tiebreak => "Tiebreak",
+        WinReason::Surrender => "Surrender",
+        WinReason::DamageThreshold => "DamageThreshold",
+    }
+}
+
+// Field names of a serialized value, with nested objects described the same way.
+fn fields(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), fields(value)))
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Number(_) => json!("number"),
+        Value::Bool(_) => json!("boolean"),
+        Value::String(_) => json!("string"),
+        Value::Array(_) => json!("array"),
+        Value::Null => json!("null"),
+    }
+}
+
+/*
+Describes the interface a host sees: the action codes with their names and
+default costs, the fields of a GameSnapshot and the GameStatus and WinReason
+variants. Everything is read off the Rust types, so front ends can generate
+their bindings from it instead of copying them by hand.
+*/
+pub fn interface_schema() -> Value {
+    let actions: Vec<Value> = ALL_ACTIONS
+        .iter()
+        .map(|action| {
+            json!({
+                "code": action.code(),
+                "name": format!("{:?}", action),
+                "damage": action.damage_amnt(),
+                "mana_cost": action.mana_cost(),
+            })
+        })
+        .collect();
+    let snapshot = serde_json::to_value(Game::new().snapshot()).expect("snapshots serialize");
+    let sides = Side::ALL.map(side_name);
+    let statuses = GameStatus::ALL.map(status_name);
+    let reasons = WinReason::ALL.map(reason_name);
+    json!({
+        "actions": actions,
+        "state": fields(&snapshot),
+        "sides": sides,
+        "game_status": statuses,
+        "win_reason": reasons,
+    })
+}
@@ -0,0 +1,260 @@
+use crate::{Action, Game, GameSnapshot, GameStatus, Side, ALL_ACTIONS};
+
+// How much each point of HP and mana lead is worth in `evaluate_position`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PositionWeights {
+    pub health: i32,
+    pub mana: i32,
+}
+
+impl Default for PositionWeights {
+    fn default() -> Self {
+        PositionWeights { health: 3, mana: 1 }
+    }
+}
+
+// Who `Game::solve` expects to come out ahead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Favorite {
+    Left,
+    Right,
+    Balanced,
+}
+
+// An action some other action beats against every reply, see `Game::dominated_actions`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Dominated {
+    pub action: Action,
+    pub dominated_by: Action,
+    pub explanation: String,
+}
+
+// A first-move pair from `Game::opening_book`, with the position it leads to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Opening {
+    pub left: Action,
+    pub right: Action,
+    pub position: GameSnapshot,
+    // Left's `evaluate_position` score after the turn.
+    pub score: i32,
+}
+
+// Score of a finished game in `search_value`, far beyond any heuristic score.
+const DECIDED: i32 = 1_000_000;
+
+impl Game {
+    // Heuristic score of the position from `side`'s point of view, 0 when even.
+    pub fn evaluate_position(&self, side: Side) -> i32 {
+        self.evaluate_position_with(side, &PositionWeights::default())
+    }
+
+    pub fn evaluate_position_with(&self, side: Side, weights: &PositionWeights) -> i32 {
+        let own = self.wizard(side);
+        let theirs = self.wizard(side.opponent());
+        let health = own.health() as i32 - theirs.health() as i32;
+        let mana = own.mana() as i32 - theirs.mana() as i32;
+        health * weights.health + mana * weights.mana
+    }
+
+    /*
+    Whether `side` can guarantee a win within `within_turns` turns, whatever the opponent plays.
+    Both sides move at once, so a move only counts if it wins (or keeps a forced win)
+    against every legal reply. Draws and losses along the way don't count as wins.
+    */
+    pub fn has_forced_win(&self, side: Side, within_turns: u32) -> bool {
+        match self.status() {
+            GameStatus::Winner(winner) => return winner == side,
+            GameStatus::Draw => return false,
+            GameStatus::Ongoing => (),
+        }
+        if within_turns == 0 {
+            return false;
+        }
+
+        let replies = self.available_actions(side.opponent());
+        self.available_actions(side).into_iter().any(|action| {
+            replies.iter().all(|&reply| {
+                let next = match side {
+                    Side::Left => self.simulate_tick(action, reply),
+                    _ => self.simulate_tick(reply, action),
+                };
+                next.is_ok_and(|next| next.has_forced_win(side, within_turns - 1))
+            })
+        })
+    }
+
+    /*
+    Who is favored under best play looking `depth` turns ahead, scoring the
+    positions left at the end with `evaluate_position`.
+    Both sides move at once, so a side is only favored if it comes out ahead
+    even when the opponent gets to answer its move (Left's maximin above 0,
+    or Right's minimax below 0). Anything in between is Balanced.
+    */
+    pub fn solve(&self, depth: u32) -> Favorite {
+        let (lower, upper) = match self.status() {
+            GameStatus::Ongoing if depth > 0 => self.value_bounds(depth),
+            _ => {
+                let value = self.search_value(0);
+                (value, value)
+            }
+        };
+        if lower > 0 {
+            Favorite::Left
+        } else if upper < 0 {
+            Favorite::Right
+        } else {
+            Favorite::Balanced
+        }
+    }
+
+    // Value of the position for Left, halfway between the pure-strategy bounds.
+    fn search_value(&self, depth: u32) -> i32 {
+        match self.status() {
+            GameStatus::Winner(Side::Left) => DECIDED,
+            GameStatus::Winner(_) => -DECIDED,
+            GameStatus::Draw => 0,
+            GameStatus::Ongoing if depth == 0 => self.evaluate_position(Side::Left),
+            GameStatus::Ongoing => {
+                let (lower, upper) = self.value_bounds(depth);
+                lower / 2 + upper / 2
+            }
+        }
+    }
+
+    // Left's best guaranteed value, and the most Right can hold Left to, over one turn.
+    fn value_bounds(&self, depth: u32) -> (i32, i32) {
+        let left_moves = self.available_actions(Side::Left);
+        let right_moves = self.available_actions(Side::Right);
+        let values: Vec<Vec<i32>> = left_moves
+            .iter()
+            .map(|&left| {
+                right_moves
+                    .iter()
+                    .map(|&right| match self.simulate_tick(left, right) {
+                        Ok(next) => next.search_value(depth - 1),
+                        Err(_) => 0,
+                    })
+                    .collect()
+            })
+            .collect();
+        let lower = values
+            .iter()
+            .map(|row| row.iter().copied().min().unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+        let upper = (0..right_moves.len())
+            .map(|column| values.iter().map(|row| row[column]).max().unwrap_or(0))
+            .min()
+            .unwrap_or(0);
+        (lower, upper)
+    }
+
+    // Every action except Surrender, the rows and columns of `action_matrix`.
+    pub fn matrix_actions() -> &'static [Action] {
+        &ALL_ACTIONS[..ALL_ACTIONS.len() - 1]
+    }
+
+    pub fn action_matrix(&self) -> Vec<Vec<Option<i32>>> {
+        self.action_matrix_with(&PositionWeights::default())
+    }
+
+    /*
+    How much Left's `evaluate_position_with` score moves for every pairing of
+    Left's action (rows) against Right's (columns) over one turn from here,
+    in `matrix_actions` order. None where either action is illegal.
+    */
+    pub fn action_matrix_with(&self, weights: &PositionWeights) -> Vec<Vec<Option<i32>>> {
+        self.swing_matrix(Side::Left, weights)
+    }
+
+    // `action_matrix_with` from `side`'s point of view, its actions down the side.
+    fn swing_matrix(&self, side: Side, weights: &PositionWeights) -> Vec<Vec<Option<i32>>> {
+        let before = self.evaluate_position_with(side, weights);
+        Game::matrix_actions()
+            .iter()
+            .map(|&own| {
+                Game::matrix_actions()
+                    .iter()
+                    .map(|&reply| {
+                        let next = match side {
+                            Side::Left => self.simulate_tick(own, reply),
+                            _ => self.simulate_tick(reply, own),
+                        }
+                        .ok()?;
+                        Some(next.evaluate_position_with(side, weights) - before)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /*
+    Actions Left should never play from here under the current SpellTable:
+    another legal action scores strictly better in `action_matrix` against
+    every legal reply. Run it on a neutral position to balance the table.
+    */
+    pub fn dominated_actions(&self) -> Vec<Dominated> {
+        let actions = Game::matrix_actions();
+        let matrix = self.action_matrix();
+        (0..actions.len())
+            .filter_map(|worse| {
+                let better = (0..actions.len()).find(|&better| beats(&matrix, better, worse))?;
+                Some(Dominated {
+                    action: actions[worse],
+                    dominated_by: actions[better],
+                    explanation: format!(
+                        "{:?} scores better than {:?} against every reply",
+                        actions[better], actions[worse]
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /*
+    Every legal pair of actions from here with the position it leaves, in
+    `available_actions` order for Left then Right. Run on a fresh game it is
+    the opening book, the few mana a wizard starts with keeps it small.
+    */
+    pub fn opening_book(&self) -> Vec<Opening> {
+        self.legal_action_pairs()
+            .into_iter()
+            .filter_map(|(left, right)| {
+                let next = self.simulate_tick(left, right).ok()?;
+                Some(Opening {
+                    left,
+                    right,
+                    position: next.snapshot(),
+                    score: next.evaluate_position(Side::Left),
+                })
+            })
+            .collect()
+    }
+
+    /*
+    A gentle hint for practice games when `action` is a clear mistake for
+    `side` from here, another legal action scoring strictly better against
+    every reply. None for anything reasonable, and for illegal actions,
+    which `tick` already reports.
+    */
+    pub fn hint(&self, side: Side, action: Action) -> Option<String> {
+        let actions = Game::matrix_actions();
+        let worse = actions.iter().position(|&other| other == action)?;
+        let matrix = self.swing_matrix(side, &PositionWeights::default());
+        let better = (0..actions.len()).find(|&better| beats(&matrix, better, worse))?;
+        Some(format!(
+            "{:?} was available and does better than {:?} whatever the opponent plays",
+            actions[better], action
+        ))
+    }
+}
+
+// Whether row `better` scores strictly more than row `worse` in every column both can play.
+fn beats(matrix: &[Vec<Option<i32>>], better: usize, worse: usize) -> bool {
+    let pairs: Vec<(i32, i32)> = matrix[better]
+        .iter()
+        .zip(&matrix[worse])
+        .filter_map(|(&a, &b)| Some((a?, b?)))
+        .collect();
+    !pairs.is_empty() && pairs.iter().all(|(a, b)| a > b)
+}
@@ -0,0 +1,203 @@
+use crate::replay::play_out;
+use crate::strategy::{strategy_by_name, RandomStrategy, Strategy};
+use crate::{Action, Game, GameConfig, GameError, GameResult, SaturationStats, Side};
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub games: u32,
+    pub config_hash: u64,
+    pub left_wins: u32,
+    pub right_wins: u32,
+    pub draws: u32,
+    // Number of games that lasted each number of turns.
+    pub game_lengths: BTreeMap<u32, u32>,
+    // How often each action was actually played, by either side.
+    pub action_counts: BTreeMap<Action, u64>,
+    // Clamped HP and mana arithmetic summed over every game, see `Game::saturation`.
+    // Defaulted so reports saved before it was added still load.
+    #[serde(default)]
+    pub saturation: SaturationStats,
+}
+
+/*
+Plays `games` games of random against random, the same matchup `main` runs.
+Both strategies are seeded from `seed`, so the same seed and config always
+give the same report.
+*/
+pub fn simulate(config: &GameConfig, games: u32, seed: u64) -> Result<SimulationReport, GameError> {
+    run(
+        config,
+        &mut RandomStrategy,
+        &mut RandomStrategy,
+        games,
+        seed,
+    )
+}
+
+/*
+Plays `games` games between the strategies named `left` and `right`
+(see `strategy_by_name`). Everything random is seeded from `seed`.
+*/
+pub fn run_matchup(
+    config: &GameConfig,
+    left: &str,
+    right: &str,
+    games: u32,
+    seed: u64,
+) -> Result<SimulationReport> {
+    let unknown = |name: &str| anyhow!("unknown strategy {:?}", name);
+    let mut left = strategy_by_name(left).ok_or_else(|| unknown(left))?;
+    let mut right = strategy_by_name(right).ok_or_else(|| unknown(right))?;
+    Ok(run(config, left.as_mut(), right.as_mut(), games, seed)?)
+}
+
+/*
+Plays `games` games between `left` and `right` and returns every game's
+result, for callers doing their own analysis. Seeded the same way as
+`simulate` and `run_matchup`, whose reports tally these same games.
+*/
+pub fn run_batch(
+    config: &GameConfig,
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    games: usize,
+    seed: u64,
+) -> Result<Vec<GameResult>, GameError> {
+    let mut results = Vec::with_capacity(games);
+    play_games(config, left, right, games, seed, |game| {
+        results.push(game.result().expect("finished game"))
+    })?;
+    Ok(results)
+}
+
+// Plays the games and hands each one to `finished` once it's over.
+fn play_games(
+    config: &GameConfig,
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    games: usize,
+    seed: u64,
+    mut finished: impl FnMut(&Game),
+) -> Result<(), GameError> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut left_rng = StdRng::seed_from_u64(rng.random());
+    let mut right_rng = StdRng::seed_from_u64(rng.random());
+    for _ in 0..games {
+        let mut game = Game::with_seed(config.clone(), rng.random())?;
+        play_out(
+            &mut game,
+            left,
+            right,
+            &mut left_rng,
+            &mut right_rng,
+            |_| (),
+        )?;
+        finished(&game);
+    }
+    Ok(())
+}
+
+fn run(
+    config: &GameConfig,
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    games: u32,
+    seed: u64,
+) -> Result<SimulationReport, GameError> {
+    let mut report = SimulationReport {
+        seed,
+        games,
+        config_hash: config.config_hash(),
+        ..SimulationReport::default()
+    };
+    play_games(config, left, right, games as usize, seed, |game| {
+        // Only moves that were played are in the history, so only those get counted.
+        for &(leftaction, rightaction) in game.history() {
+            *report.action_counts.entry(leftaction).or_default() += 1;
+            *report.action_counts.entry(rightaction).or_default() += 1;
+        }
+        match game.game_completed().1 {
+            Side::Left => report.left_wins += 1,
+            Side::Right => report.right_wins += 1,
+            Side::Neither => report.draws += 1,
+        }
+        *report.game_lengths.entry(game.turn_count()).or_default() += 1;
+        report.saturation.merge(&game.saturation());
+    })?;
+    Ok(report)
+}
+
+// How efficiently a strategy spends its mana, see `analyze_strategy`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct StrategyStats {
+    pub games: u32,
+    pub mana_spent: u64,
+    /*
+    Mana spent on spells that did nothing: attacks and ManaBurns that were
+    shielded, reflected or dodged, and defences cast when nothing came in.
+    Illegal picks are asked again and never cost mana, so they don't count.
+    */
+    pub wasted_mana: u64,
+}
+
+impl StrategyStats {
+    pub fn wasted_per_game(&self) -> f64 {
+        self.wasted_mana as f64 / self.games.max(1) as f64
+    }
+}
+
+/*
+Plays `games` games of `strategy` on the left against `opponent` and adds
+up the mana it spent and wasted, as the wizard counted it. Seeded the same
+way as `run_batch`.
+*/
+pub fn analyze_strategy(
+    config: &GameConfig,
+    strategy: &mut dyn Strategy,
+    opponent: &mut dyn Strategy,
+    games: u32,
+    seed: u64,
+) -> Result<StrategyStats, GameError> {
+    let mut stats = StrategyStats {
+        games,
+        ..StrategyStats::default()
+    };
+    play_games(config, strategy, opponent, games as usize, seed, |game| {
+        let wizard = game.wizard(Side::Left);
+        stats.mana_spent += wizard.mana_spent as u64;
+        stats.wasted_mana += wizard.mana_wasted as u64;
+    })?;
+    Ok(stats)
+}
+
+pub fn write_report(report: &SimulationReport, path: impl AsRef<Path>) -> Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(file, report)?;
+    Ok(())
+}
+
+pub fn read_report(path: impl AsRef<Path>) -> Result<SimulationReport> {
+    let file = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(file)?)
+}
+
+// Runs a simulation and saves its report as JSON, for long runs that get analysed later.
+pub fn simulate_to_file(
+    config: &GameConfig,
+    games: u32,
+    seed: u64,
+    path: impl AsRef<Path>,
+) -> Result<SimulationReport> {
+    let report = simulate(config, games, seed)?;
+    write_report(&report, path)?;
+    Ok(report)
+}
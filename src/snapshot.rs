@@ -0,0 +1,120 @@
+use crate::replay::WizardState;
+use crate::{Action, Game, GameStatus, Side, ALL_ACTIONS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Everything a UI needs to show about a position.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub turn: u32,
+    pub left: WizardState,
+    pub right: WizardState,
+    pub status: GameStatus,
+}
+
+// One button of the action bar, see `Game::preview_all`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ActionPreview {
+    pub action: Action,
+    // `Action::code`, for hosts passing actions as integers.
+    pub code: u8,
+    pub legal: bool,
+    // Negative for actions that restore mana.
+    pub mana_cost: i8,
+    // The most damage it can do this turn: stored charges for Release, top of the variance roll.
+    pub best_case_damage: u8,
+}
+
+// Mixed strategy as odds per action, normalized over the legal ones.
+pub type ActionMix = HashMap<Action, f64>;
+
+impl Game {
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            turn: self.turn_count(),
+            left: self.wizard(Side::Left).into(),
+            right: self.wizard(Side::Right).into(),
+            status: self.status(),
+        }
+    }
+
+    /*
+    Everything the action bar shows for the wizard on `side`, one entry per
+    action in ALL_ACTIONS order, so a front end renders it from one call
+    instead of a getter per button. Serializes to a plain JSON array.
+    */
+    pub fn preview_all(&self, side: Side) -> Vec<ActionPreview> {
+        let spells = &self.config().spells;
+        let wizard = self.wizard(side);
+        ALL_ACTIONS
+            .iter()
+            .map(|&action| {
+                let mut damage = spells.damage(action);
+                if action == Action::Release {
+                    damage = damage.saturating_mul(wizard.charges());
+                }
+                if damage > 0 {
+                    damage = damage.saturating_add(self.config().damage_variance);
+                }
+                ActionPreview {
+                    action,
+                    code: action.code(),
+                    legal: self.can_cast(side, action),
+                    mana_cost: spells.mana_cost(action),
+                    best_case_damage: damage,
+                }
+            })
+            .collect()
+    }
+
+    /*
+    Every position one turn from now with its probability, when both sides
+    play the given mixes. Illegal and non-positive entries are dropped and the
+    rest rescaled, a mix with nothing legal left plays Concentrate.
+    Pairs leading to the same position are merged, so the probabilities sum to 1.
+    Damage variance is rolled from the game's RNG like `simulate_tick`.
+    */
+    pub fn outcome_distribution(
+        &self,
+        left_mix: &ActionMix,
+        right_mix: &ActionMix,
+    ) -> Vec<(GameSnapshot, f64)> {
+        let left = self.legal_mix(Side::Left, left_mix);
+        let right = self.legal_mix(Side::Right, right_mix);
+        let mut outcomes: Vec<(GameSnapshot, f64)> = Vec::new();
+        for &(left_action, left_odds) in &left {
+            for &(right_action, right_odds) in &right {
+                let Ok(next) = self.simulate_tick(left_action, right_action) else {
+                    continue;
+                };
+                let snapshot = next.snapshot();
+                let odds = left_odds * right_odds;
+                match outcomes.iter_mut().find(|(seen, _)| *seen == snapshot) {
+                    Some((_, total)) => *total += odds,
+                    None => outcomes.push((snapshot, odds)),
+                }
+            }
+        }
+        outcomes
+    }
+
+    // In `available_actions` order so the result doesn't depend on HashMap order.
+    fn legal_mix(&self, side: Side, mix: &ActionMix) -> Vec<(Action, f64)> {
+        let legal: Vec<(Action, f64)> = self
+            .available_actions(side)
+            .into_iter()
+            .filter_map(|action| {
+                let odds = mix.get(&action).copied().unwrap_or(0.0);
+                (odds > 0.0).then_some((action, odds))
+            })
+            .collect();
+        if legal.is_empty() {
+            return vec![(Action::Concentrate, 1.0)];
+        }
+        let total: f64 = legal.iter().map(|(_, odds)| odds).sum();
+        legal
+            .into_iter()
+            .map(|(action, odds)| (action, odds / total))
+            .collect()
+    }
+}
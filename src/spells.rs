@@ -0,0 +1,99 @@
+use crate::{Action, GameError, ALL_ACTIONS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct Spell {
+    pub damage: u8,
+    // Negative costs restore mana instead, like Concentrate.
+    pub mana_cost: i8,
+}
+
+/*
+Damage and mana cost of every action for a game.
+Defaults to the values on `Action`, but can be tweaked per game
+for balance experiments without touching the engine.
+*/
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SpellTable {
+    spells: HashMap<Action, Spell>,
+}
+
+impl Default for SpellTable {
+    fn default() -> Self {
+        let spells = ALL_ACTIONS
+            .iter()
+            .map(|&action| {
+                let spell = Spell {
+                    damage: action.damage_amnt(),
+                    mana_cost: action.mana_cost(),
+                };
+                (action, spell)
+            })
+            .collect();
+        SpellTable { spells }
+    }
+}
+
+// Hashed in ALL_ACTIONS order, so equal tables always hash the same.
+impl Hash for SpellTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for action in ALL_ACTIONS {
+            action.hash(state);
+            self.get(action).hash(state);
+        }
+    }
+}
+
+impl SpellTable {
+    pub fn get(&self, action: Action) -> Spell {
+        self.spells[&action]
+    }
+
+    pub fn set(&mut self, action: Action, spell: Spell) {
+        self.spells.insert(action, spell);
+    }
+
+    pub fn damage(&self, action: Action) -> u8 {
+        self.get(action).damage
+    }
+
+    pub fn mana_cost(&self, action: Action) -> i8 {
+        self.get(action).mana_cost
+    }
+
+    // Mana paid up front to cast `action`, 0 for actions that restore mana.
+    pub fn mana_spent(&self, action: Action) -> u8 {
+        self.mana_cost(action).max(0) as u8
+    }
+
+    // Mana restored by `action`, on top of the passive gain.
+    pub fn mana_gained(&self, action: Action) -> u8 {
+        self.mana_cost(action).min(0).unsigned_abs()
+    }
+
+    /*
+    Catches tables that break what the engine assumes about each kind of action:
+    attacks deal damage, defences don't, and only Concentrate and BloodMagic
+    restore mana.
+    */
+    pub fn validate(&self) -> Result<(), GameError> {
+        for action in ALL_ACTIONS {
+            let spell = self.get(action);
+            if action.is_attack() && spell.damage == 0 {
+                return Err(GameError::InvalidConfig("attacks must deal damage"));
+            }
+            if action.is_defensive() && spell.damage > 0 {
+                return Err(GameError::InvalidConfig("defences can't deal damage"));
+            }
+            let restores = matches!(action, Action::Concentrate | Action::BloodMagic);
+            if !restores && spell.mana_cost < 0 {
+                return Err(GameError::InvalidConfig(
+                    "only Concentrate and BloodMagic can restore mana",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{Action, Game, GameError, GameStatus, Side, ALL_ACTIONS};
+use rand::prelude::IndexedRandom;
+use rand::RngCore;
+use std::collections::HashMap;
+
+pub trait Strategy {
+    /*
+    Picks the action for the wizard on `side` this turn.
+    Strategies draw all their randomness from `rng`, each side getting its own,
+    so a game can be replayed by seeding both the same way again.
+    */
+    fn choose(&mut self, game: &Game, side: Side, rng: &mut dyn RngCore) -> Action;
+
+    // Human readable reason for the last choice, for strategies that can give one.
+    fn explain(&self) -> Option<String> {
+        None
+    }
+}
+
+// Picks any action uniformly, legal or not. Never surrenders.
+#[derive(Default)]
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn choose(&mut self, _game: &Game, _side: Side, rng: &mut dyn RngCore) -> Action {
+        *ALL_ACTIONS[..ALL_ACTIONS.len() - 1].choose(rng).unwrap()
+    }
+}
+
+/*
+Plays the biggest affordable attack while ahead or even on HP,
+and turtles up (Reflect, then ManaShield, then Concentrate) once
+it falls behind by more than `threshold` HP.
+*/
+pub struct AdaptiveStrategy {
+    pub threshold: u8,
+    reason: Option<String>,
+}
+
+impl AdaptiveStrategy {
+    pub fn new(threshold: u8) -> AdaptiveStrategy {
+        AdaptiveStrategy {
+            threshold,
+            reason: None,
+        }
+    }
+}
+
+impl Default for AdaptiveStrategy {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+impl Strategy for AdaptiveStrategy {
+    fn choose(&mut self, game: &Game, side: Side, _rng: &mut dyn RngCore) -> Action {
+        let own = game.wizard(side).health();
+        let theirs = game.wizard(side.opponent()).health();
+        let behind = theirs.saturating_sub(own);
+
+        let defensive = behind > self.threshold;
+        let preference: &[Action] = if defensive {
+            &[Action::Reflect, Action::ManaShield, Action::Concentrate]
+        } else {
+            &[Action::LightningBolt, Action::Fireball, Action::Strike]
+        };
+        let action = preference
+            .iter()
+            .copied()
+            .find(|&action| game.can_cast(side, action))
+            .unwrap_or(Action::Concentrate);
+
+        self.reason = Some(if defensive {
+            format!("behind by {} HP, playing safe with {:?}", behind, action)
+        } else {
+            format!(
+                "not behind by more than {} HP, attacking with the biggest affordable spell, {:?}",
+                self.threshold, action
+            )
+        });
+        action
+    }
+
+    fn explain(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+/*
+Looks one turn ahead and plays the action whose worst reply still leaves the
+best `evaluate_position` score, both sides moving at once.
+*/
+#[derive(Default)]
+pub struct MinimaxStrategy;
+
+impl Strategy for MinimaxStrategy {
+    fn choose(&mut self, game: &Game, side: Side, _rng: &mut dyn RngCore) -> Action {
+        let replies = game.available_actions(side.opponent());
+        let worst_case = |action: Action| {
+            replies
+                .iter()
+                .filter_map(|&reply| match side {
+                    Side::Left => game.simulate_tick(action, reply).ok(),
+                    _ => game.simulate_tick(reply, action).ok(),
+                })
+                .map(|next| match next.status() {
+                    GameStatus::Winner(winner) if winner == side => i32::MAX,
+                    GameStatus::Winner(_) => i32::MIN,
+                    _ => next.evaluate_position(side),
+                })
+                .min()
+                .unwrap_or(i32::MIN)
+        };
+        game.available_actions(side)
+            .into_iter()
+            .filter(|&action| action != Action::Surrender)
+            .max_by_key(|&action| worst_case(action))
+            .unwrap_or(Action::Concentrate)
+    }
+}
+
+/*
+Full-information play around a flush opponent. Once the opponent holds
+`margin` more mana than its priciest attack costs, or sits within `margin`
+of `max_mana`, it can afford anything and gains little by waiting, so it's
+expected to attack: a ManaShield there turns that mana into waste.
+Otherwise it attacks with the biggest affordable spell, keeping one mana
+back for the shield when it can.
+*/
+pub struct PressureStrategy {
+    pub margin: u8,
+}
+
+impl Default for PressureStrategy {
+    fn default() -> Self {
+        PressureStrategy { margin: 2 }
+    }
+}
+
+impl Strategy for PressureStrategy {
+    fn choose(&mut self, game: &Game, side: Side, _rng: &mut dyn RngCore) -> Action {
+        let config = game.config();
+        let theirs = game.wizard(side.opponent()).mana();
+        let priciest = ALL_ACTIONS
+            .into_iter()
+            .filter(Action::is_attack)
+            .map(|action| config.spells.mana_spent(action))
+            .max()
+            .unwrap_or(0);
+        let flush = theirs
+            >= priciest
+                .saturating_add(self.margin)
+                .min(config.max_mana.saturating_sub(self.margin));
+        // Only attacks that cost mana can waste it.
+        let threatened = game
+            .available_actions(side.opponent())
+            .iter()
+            .any(|&action| action.is_attack() && config.spells.mana_spent(action) > 0);
+        if flush && threatened && game.can_cast(side, Action::ManaShield) {
+            return Action::ManaShield;
+        }
+        let own = game.wizard(side).mana();
+        let shield = config.spells.mana_spent(Action::ManaShield);
+        [Action::LightningBolt, Action::Fireball, Action::Strike]
+            .into_iter()
+            .find(|&action| {
+                game.can_cast(side, action) && own >= config.spells.mana_spent(action) + shield
+            })
+            .or_else(|| Some(Action::Strike).filter(|&strike| game.can_cast(side, strike)))
+            .unwrap_or(Action::Concentrate)
+    }
+}
+
+// Strategies by the names the command line uses.
+pub fn strategy_by_name(name: &str) -> Option<Box<dyn Strategy>> {
+    let strategy: Box<dyn Strategy> = match name {
+        "random" => Box::new(RandomStrategy),
+        "adaptive" => Box::new(AdaptiveStrategy::default()),
+        // Never falls back to defence, always the biggest affordable attack.
+        "greedy" => Box::new(AdaptiveStrategy::new(u8::MAX)),
+        "minimax" => Box::new(MinimaxStrategy),
+        "pressure" => Box::new(PressureStrategy::default()),
+        _ => return None,
+    };
+    Some(strategy)
+}
+
+/*
+Picks among the currently legal actions in proportion to their weights.
+Actions missing from `weights` are never picked, and a wizard with no
+weighted legal action falls back to Concentrate.
+*/
+pub struct WeightedRandomStrategy {
+    pub weights: HashMap<Action, f64>,
+}
+
+impl WeightedRandomStrategy {
+    pub fn new(weights: HashMap<Action, f64>) -> WeightedRandomStrategy {
+        WeightedRandomStrategy { weights }
+    }
+}
+
+impl Strategy for WeightedRandomStrategy {
+    fn choose(&mut self, game: &Game, side: Side, rng: &mut dyn RngCore) -> Action {
+        let legal = game.available_actions(side);
+        let weights = &self.weights;
+        legal
+            .choose_weighted(rng, |action| weights.get(action).copied().unwrap_or(0.0))
+            .copied()
+            .unwrap_or(Action::Concentrate)
+    }
+}
+
+impl Game {
+    /*
+    Makes the next `step_with` play `action` for the wizard on `side` instead
+    of asking its strategy, for scripting scenarios and debugging. Used up by
+    the first step that plays a turn: if `action` is illegal by then the step
+    returns the error, the turn isn't played and the next step tries it again.
+    `tick` ignores it.
+    */
+    pub fn force_next_action(&mut self, side: Side, action: Action) {
+        self.wizard_mut(side).forced = Some((action, false));
+    }
+
+    // Like `force_next_action`, but an illegal `action` leaves the pick to the strategy.
+    pub fn force_next_action_or_fallback(&mut self, side: Side, action: Action) {
+        self.wizard_mut(side).forced = Some((action, true));
+    }
+
+    fn pick(&mut self, strategy: &mut dyn Strategy, side: Side, rng: &mut dyn RngCore) -> Action {
+        match self.wizard(side).forced {
+            Some((action, fallback)) if !fallback || self.can_cast(side, action) => action,
+            _ => strategy.choose(self, side, rng),
+        }
+    }
+
+    /*
+    Plays exactly one turn with the actions `left` and `right` choose, for hosts
+    that drive an AI game a frame at a time. Returns the final status once the
+    game is over and None while it goes on. An illegal choice is returned as
+    the error and the turn isn't played.
+    */
+    pub fn step_with(
+        &mut self,
+        left: &mut dyn Strategy,
+        right: &mut dyn Strategy,
+        left_rng: &mut dyn RngCore,
+        right_rng: &mut dyn RngCore,
+    ) -> Result<Option<GameStatus>, GameError> {
+        // The opponent of a wizard with foresight picks first, and commits.
+        let (leftaction, rightaction) = if self.has_foresight(Side::Left) {
+            let rightaction = self.pick(right, Side::Right, right_rng);
+            self.commit(Side::Right, rightaction);
+            (self.pick(left, Side::Left, left_rng), rightaction)
+        } else {
+            let leftaction = self.pick(left, Side::Left, left_rng);
+            if self.has_foresight(Side::Right) {
+                self.commit(Side::Left, leftaction);
+            }
+            (leftaction, self.pick(right, Side::Right, right_rng))
+        };
+        self.tick(leftaction, rightaction)?;
+        self.left_wizard.forced = None;
+        self.right_wizard.forced = None;
+        Ok(match self.status() {
+            GameStatus::Ongoing => None,
+            status => Some(status),
+        })
+    }
+}
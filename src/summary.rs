@@ -0,0 +1,58 @@
+use crate::{Action, Game, Side, WinReason};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// One wizard's totals over a whole game, see `Game::summary`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct WizardStats {
+    // Damage this wizard's spells landed, before clamping at 0 HP.
+    pub damage_dealt: u32,
+    // HP actually lost and regained, so starting HP - damage_taken + healing_done is what's left.
+    pub damage_taken: u32,
+    pub healing_done: u32,
+    pub mana_spent: u32,
+    pub mana_wasted: u32,
+    // Every action played, combos counted as what they played.
+    pub action_counts: BTreeMap<Action, u32>,
+}
+
+// The post-match screen for a finished game.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct GameSummary {
+    pub winner: Option<Side>,
+    pub reason: WinReason,
+    pub turns: u32,
+    pub left_stats: WizardStats,
+    pub right_stats: WizardStats,
+}
+
+impl Game {
+    fn wizard_stats(&self, side: Side) -> WizardStats {
+        let wizard = self.wizard(side);
+        let mut action_counts = BTreeMap::new();
+        for &(left, right) in self.history() {
+            let action = if side == Side::Left { left } else { right };
+            *action_counts.entry(action).or_insert(0) += 1;
+        }
+        WizardStats {
+            damage_dealt: wizard.damage_dealt,
+            damage_taken: wizard.damage_taken,
+            healing_done: wizard.healing_done,
+            mana_spent: wizard.mana_spent,
+            mana_wasted: wizard.mana_wasted,
+            action_counts,
+        }
+    }
+
+    // Totals for both wizards once the game is over, None while it goes on.
+    pub fn summary(&self) -> Option<GameSummary> {
+        let result = self.result()?;
+        Some(GameSummary {
+            winner: result.winner,
+            reason: result.reason,
+            turns: result.turns,
+            left_stats: self.wizard_stats(Side::Left),
+            right_stats: self.wizard_stats(Side::Right),
+        })
+    }
+}
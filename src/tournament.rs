@@ -0,0 +1,307 @@
+use crate::replay::play_out;
+use crate::strategy::Strategy;
+use crate::{Game, GameConfig, GameError, Side};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+pub struct Entrant {
+    pub name: String,
+    pub strategy: Box<dyn Strategy>,
+}
+
+impl Entrant {
+    pub fn new(name: impl Into<String>, strategy: impl Strategy + 'static) -> Entrant {
+        Entrant {
+            name: name.into(),
+            strategy: Box::new(strategy),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MatchResult {
+    pub left: String,
+    pub right: String,
+    pub left_wins: u32,
+    pub right_wins: u32,
+    pub draws: u32,
+}
+
+impl MatchResult {
+    pub fn games(&self) -> u32 {
+        self.left_wins + self.right_wins + self.draws
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TournamentResult {
+    pub matches: Vec<MatchResult>,
+}
+
+// Plays one game seeded with `seed` to completion and returns the winning side,
+// see `replay::play_out`.
+pub(crate) fn play(
+    left: &mut dyn Strategy,
+    right: &mut dyn Strategy,
+    config: &GameConfig,
+    seed: u64,
+    left_rng: &mut dyn RngCore,
+    right_rng: &mut dyn RngCore,
+) -> Result<Side, GameError> {
+    let mut game = Game::with_seed(config.clone(), seed)?;
+    play_out(&mut game, left, right, left_rng, right_rng, |_| ())?;
+    Ok(game.game_completed().1)
+}
+
+/*
+Every entrant plays `games_per_match` games against every other entrant,
+the earlier entrant taking the left side.
+Set `config.max_turns` when entering strategies that can stall each other forever.
+Each side of every match gets its own RNG and every game its own game seed,
+all drawn from `seed`, so the same seed and entrants always give the same
+result while damage_variance and coin flips still differ from game to game.
+*/
+pub fn round_robin(
+    entrants: &mut [Entrant],
+    games_per_match: u32,
+    config: &GameConfig,
+    seed: u64,
+) -> Result<TournamentResult, GameError> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut result = TournamentResult::default();
+    for i in 0..entrants.len() {
+        let (head, tail) = entrants.split_at_mut(i + 1);
+        let left = &mut head[i];
+        for right in tail.iter_mut() {
+            let mut record = MatchResult {
+                left: left.name.clone(),
+                right: right.name.clone(),
+                left_wins: 0,
+                right_wins: 0,
+                draws: 0,
+            };
+            let mut left_rng = StdRng::seed_from_u64(rng.random());
+            let mut right_rng = StdRng::seed_from_u64(rng.random());
+            for _ in 0..games_per_match {
+                let winner = play(
+                    left.strategy.as_mut(),
+                    right.strategy.as_mut(),
+                    config,
+                    rng.random(),
+                    &mut left_rng,
+                    &mut right_rng,
+                )?;
+                match winner {
+                    Side::Left => record.left_wins += 1,
+                    Side::Right => record.right_wins += 1,
+                    Side::Neither => record.draws += 1,
+                }
+            }
+            result.matches.push(record);
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Elimination {
+    Single,
+    // Knocked out on the second loss. The two finalists play one grand final, no reset.
+    Double,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BracketMatch {
+    // Counting from 1. Winners and losers bracket matches of the same round share it.
+    pub round: u32,
+    pub losers_bracket: bool,
+    pub winner: String,
+    // None when `winner` had a bye.
+    pub result: Option<MatchResult>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bracket {
+    // In the order they were played.
+    pub matches: Vec<BracketMatch>,
+    pub champion: String,
+}
+
+impl Bracket {
+    pub fn rounds(&self) -> u32 {
+        self.matches
+            .iter()
+            .map(|played| played.round)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+// Both entrants at `left` < `right` mutably at once.
+fn pair_mut(entrants: &mut [Entrant], left: usize, right: usize) -> (&mut Entrant, &mut Entrant) {
+    let (head, tail) = entrants.split_at_mut(right);
+    (&mut head[left], &mut tail[0])
+}
+
+// A best-of-`best_of` match, stopping once either side can't be caught.
+// A tie on wins, draws included, goes to `left` as the higher seed.
+fn play_match(
+    left: &mut Entrant,
+    right: &mut Entrant,
+    best_of: u32,
+    config: &GameConfig,
+    rng: &mut StdRng,
+) -> Result<(MatchResult, bool), GameError> {
+    let mut record = MatchResult {
+        left: left.name.clone(),
+        right: right.name.clone(),
+        left_wins: 0,
+        right_wins: 0,
+        draws: 0,
+    };
+    let mut left_rng = StdRng::seed_from_u64(rng.random());
+    let mut right_rng = StdRng::seed_from_u64(rng.random());
+    let needed = best_of / 2 + 1;
+    while record.games() < best_of && record.left_wins < needed && record.right_wins < needed {
+        let winner = play(
+            left.strategy.as_mut(),
+            right.strategy.as_mut(),
+            config,
+            rng.random(),
+            &mut left_rng,
+            &mut right_rng,
+        )?;
+        match winner {
+            Side::Left => record.left_wins += 1,
+            Side::Right => record.right_wins += 1,
+            Side::Neither => record.draws += 1,
+        }
+    }
+    let left_won = record.left_wins >= record.right_wins;
+    Ok((record, left_won))
+}
+
+// Everything a bracket needs while it runs.
+struct BracketRun<'a> {
+    entrants: &'a mut [Entrant],
+    best_of: u32,
+    config: &'a GameConfig,
+    rng: StdRng,
+    matches: Vec<BracketMatch>,
+}
+
+impl BracketRun<'_> {
+    // Plays `high` against `low` and records it, returning the winner then the loser.
+    fn play(
+        &mut self,
+        high: usize,
+        low: usize,
+        round: u32,
+        losers_bracket: bool,
+    ) -> Result<(usize, usize), GameError> {
+        let (left, right) = pair_mut(self.entrants, high, low);
+        let (result, left_won) = play_match(left, right, self.best_of, self.config, &mut self.rng)?;
+        let (winner, loser) = if left_won { (high, low) } else { (low, high) };
+        self.matches.push(BracketMatch {
+            round,
+            losers_bracket,
+            winner: self.entrants[winner].name.clone(),
+            result: Some(result),
+        });
+        Ok((winner, loser))
+    }
+
+    // One round of `pool`, in seed order. Returns who went through and who lost.
+    fn round(
+        &mut self,
+        pool: &[usize],
+        round: u32,
+        losers_bracket: bool,
+    ) -> Result<(Vec<usize>, Vec<usize>), GameError> {
+        // Nobody to play, the lone entrant waits for the next round.
+        if pool.len() < 2 {
+            return Ok((pool.to_vec(), Vec::new()));
+        }
+        let mut through = Vec::new();
+        let mut beaten = Vec::new();
+        // An odd pool gives the top seed a bye, the rest play top against bottom.
+        let mut rest = pool;
+        if pool.len() % 2 == 1 {
+            through.push(pool[0]);
+            self.matches.push(BracketMatch {
+                round,
+                losers_bracket,
+                winner: self.entrants[pool[0]].name.clone(),
+                result: None,
+            });
+            rest = &pool[1..];
+        }
+        for index in 0..rest.len() / 2 {
+            let (top, bottom) = (rest[index], rest[rest.len() - 1 - index]);
+            let (winner, loser) =
+                self.play(top.min(bottom), top.max(bottom), round, losers_bracket)?;
+            through.push(winner);
+            beaten.push(loser);
+        }
+        // Back in seed order, so byes and pairings stay fair in the next round.
+        through.sort_unstable();
+        beaten.sort_unstable();
+        Ok((through, beaten))
+    }
+}
+
+/*
+Runs an elimination bracket over `entrants`, seeded in the order given, with
+a best-of-`best_of` match for every pairing. Fields that aren't a power of
+two give the top seeds byes. The higher seed always takes the left side.
+Each match gets its own RNGs seeded from `seed`, so the same seed and
+entrants always give the same bracket.
+*/
+pub fn bracket(
+    entrants: &mut [Entrant],
+    format: Elimination,
+    best_of: u32,
+    config: &GameConfig,
+    seed: u64,
+) -> Result<Bracket, GameError> {
+    if entrants.is_empty() {
+        return Err(GameError::InvalidConfig("a bracket needs entrants"));
+    }
+    let mut winners: Vec<usize> = (0..entrants.len()).collect();
+    let mut losers: Vec<usize> = Vec::new();
+    let mut run = BracketRun {
+        entrants,
+        best_of: best_of.max(1),
+        config,
+        rng: StdRng::seed_from_u64(seed),
+        matches: Vec::new(),
+    };
+    let mut round = 0;
+
+    while winners.len() + losers.len() > 2 || (winners.len() == 2 && losers.is_empty()) {
+        round += 1;
+        let (through, dropped) = run.round(&winners, round, false)?;
+        winners = through;
+        if format == Elimination::Double {
+            // Whoever just dropped down waits for the next losers round.
+            let (through, _) = run.round(&losers, round, true)?;
+            losers = through;
+            losers.extend(dropped);
+            losers.sort_unstable();
+        }
+    }
+
+    let champion = match (winners.as_slice(), losers.as_slice()) {
+        (&[champion], []) => champion,
+        (&[finalist], &[challenger]) => {
+            let high = finalist.min(challenger);
+            let low = finalist.max(challenger);
+            run.play(high, low, round + 1, false)?.0
+        }
+        _ => unreachable!("the loop runs until two entrants or fewer are left"),
+    };
+    Ok(Bracket {
+        champion: run.entrants[champion].name.clone(),
+        matches: run.matches,
+    })
+}
@@ -0,0 +1,233 @@
+use crate::replay::play_out;
+use crate::strategy::{AdaptiveStrategy, RandomStrategy, Strategy, WeightedRandomStrategy};
+use crate::tournament::play;
+use crate::{Action, Game, GameConfig, Side, ALL_ACTIONS};
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const GAMES_PER_OPPONENT: u32 = 10;
+const MUTATION_RATE: f64 = 0.2;
+const MUTATION_SIZE: f64 = 0.2;
+
+type Weights = HashMap<Action, f64>;
+
+// The actions that get a weight. Surrender throws the game and Combo does nothing
+// without a queued combo, so neither is worth evolving.
+fn genome() -> impl Iterator<Item = Action> {
+    ALL_ACTIONS
+        .into_iter()
+        .filter(|action| !matches!(action, Action::Surrender | Action::Combo))
+}
+
+// Scales the weights so they sum to 1, or spreads them evenly if they are all zero.
+// Summed in `genome` order, HashMap order would make the rounding differ between runs.
+fn normalize(weights: &mut Weights) {
+    let total: f64 = genome().map(|action| weights[&action]).sum();
+    let count = weights.len() as f64;
+    for weight in weights.values_mut() {
+        *weight = if total > 0.0 {
+            *weight / total
+        } else {
+            1.0 / count
+        };
+    }
+}
+
+fn random_weights(rng: &mut StdRng) -> Weights {
+    let mut weights = genome()
+        .map(|action| (action, rng.random::<f64>()))
+        .collect();
+    normalize(&mut weights);
+    weights
+}
+
+// Uniform crossover followed by mutation.
+fn breed(mother: &Weights, father: &Weights, rng: &mut StdRng) -> Weights {
+    let mut child: Weights = genome()
+        .map(|action| {
+            let parent = if rng.random_bool(0.5) { mother } else { father };
+            let mut weight = parent[&action];
+            if rng.random_bool(MUTATION_RATE) {
+                weight += rng.random_range(-MUTATION_SIZE..=MUTATION_SIZE);
+            }
+            (action, weight.max(0.0))
+        })
+        .collect();
+    normalize(&mut child);
+    child
+}
+
+// The fixed opponents every candidate is scored against, from defensive to all-out aggressive.
+fn opponent_pool() -> Vec<AdaptiveStrategy> {
+    vec![
+        AdaptiveStrategy::new(0),
+        AdaptiveStrategy::default(),
+        AdaptiveStrategy::new(u8::MAX),
+    ]
+}
+
+// Win rate against the opponent pool, draws counting half.
+fn fitness(weights: &Weights, config: &GameConfig, rng: &mut StdRng) -> f64 {
+    let mut score = 0.0;
+    let mut games = 0;
+    for mut opponent in opponent_pool() {
+        for _ in 0..GAMES_PER_OPPONENT {
+            let mut candidate = WeightedRandomStrategy::new(weights.clone());
+            let mut candidate_rng = StdRng::seed_from_u64(rng.random());
+            // The adaptive opponents never draw from their RNG.
+            let winner = play(
+                &mut candidate,
+                &mut opponent,
+                config,
+                rng.random(),
+                &mut candidate_rng,
+                rng,
+            )
+            .expect("valid config");
+            score += match winner {
+                Side::Left => 1.0,
+                Side::Right => 0.0,
+                Side::Neither => 0.5,
+            };
+            games += 1;
+        }
+    }
+    score / games as f64
+}
+
+/*
+Evolves action weights for a WeightedRandomStrategy with a small genetic algorithm.
+Every action but Surrender and Combo gets a weight.
+Each generation the fitter half survives and breeds the rest of the population.
+The result only depends on the arguments, so a seed always trains the same weights.
+*/
+pub fn train_weights(generations: usize, population: usize, seed: u64) -> HashMap<Action, f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let config = GameConfig {
+        max_turns: Some(100),
+        ..GameConfig::default()
+    };
+    let population = population.max(2);
+    let mut candidates: Vec<Weights> = (0..population).map(|_| random_weights(&mut rng)).collect();
+
+    for _ in 0..generations {
+        let mut scored: Vec<(f64, Weights)> = candidates
+            .into_iter()
+            .map(|weights| (fitness(&weights, &config, &mut rng), weights))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let survivors: Vec<Weights> = scored
+            .into_iter()
+            .take(population.div_ceil(2))
+            .map(|(_, weights)| weights)
+            .collect();
+        candidates = survivors.clone();
+        while candidates.len() < population {
+            let mother = &survivors[rng.random_range(0..survivors.len())];
+            let father = &survivors[rng.random_range(0..survivors.len())];
+            candidates.push(breed(mother, father, &mut rng));
+        }
+    }
+
+    // Survivors are kept in fitness order, so the best of the last generation comes first.
+    candidates.swap_remove(0)
+}
+
+// One decision of the reference strategy, seen from its own side.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TrainingRow {
+    pub turn: u32,
+    pub own_health: u8,
+    pub own_mana: u8,
+    pub opponent_health: u8,
+    pub opponent_mana: u8,
+    pub action: Action,
+}
+
+/*
+Plays `games` games of `strategy` against a seeded random opponent, swapping
+sides every game, and writes every move `strategy` got to play as a JSON
+array of TrainingRow for imitation learning. Returns the number of rows.
+*/
+pub fn export_training_data(
+    strategy: &mut dyn Strategy,
+    games: u32,
+    seed: u64,
+    path: impl AsRef<Path>,
+) -> Result<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let config = GameConfig {
+        max_turns: Some(100),
+        ..GameConfig::default()
+    };
+    let mut strategy_rng = StdRng::seed_from_u64(rng.random());
+    let mut opponent_rng = StdRng::seed_from_u64(rng.random());
+    let mut rows = Vec::new();
+
+    for index in 0..games {
+        let side = if index % 2 == 0 {
+            Side::Left
+        } else {
+            Side::Right
+        };
+        let mut game = Game::with_seed(config.clone(), rng.random())?;
+        let mut opponent = RandomStrategy;
+        let (left, right, left_rng, right_rng): (&mut dyn Strategy, &mut dyn Strategy, _, _) =
+            match side {
+                Side::Left => (
+                    strategy,
+                    &mut opponent,
+                    &mut strategy_rng,
+                    &mut opponent_rng,
+                ),
+                _ => (
+                    &mut opponent,
+                    strategy,
+                    &mut opponent_rng,
+                    &mut strategy_rng,
+                ),
+            };
+        // Each row is the position before a turn and what `strategy` ended up playing in it,
+        // so a Compel or a combo step is labelled with the action that resolved.
+        let position = |game: &Game| {
+            let own = game.wizard(side);
+            let theirs = game.wizard(side.opponent());
+            (
+                game.turn_count(),
+                own.health(),
+                own.mana(),
+                theirs.health(),
+                theirs.mana(),
+            )
+        };
+        let mut before = position(&game);
+        play_out(&mut game, left, right, left_rng, right_rng, |game| {
+            let &(left_action, right_action) = game.history().last().expect("turn was played");
+            let (turn, own_health, own_mana, opponent_health, opponent_mana) = before;
+            rows.push(TrainingRow {
+                turn,
+                own_health,
+                own_mana,
+                opponent_health,
+                opponent_mana,
+                action: if side == Side::Left {
+                    left_action
+                } else {
+                    right_action
+                },
+            });
+            before = position(game);
+        })?;
+    }
+
+    let file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(file, &rows)?;
+    Ok(rows.len())
+}
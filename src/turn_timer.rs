@@ -0,0 +1,82 @@
+use crate::{Action, Game, GameError, Side};
+
+/*
+Collects the two moves of a simultaneous turn for hosts taking them from
+players over a connection, so a player who never answers can't stall the
+game. Once `window_ms` has passed since the turn opened, whoever hasn't
+submitted plays `default_action`, or Concentrate if that isn't legal for
+them. Times are milliseconds on any clock the host likes, passed in rather
+than read here since std's clock isn't available on wasm32.
+*/
+pub struct TurnTimer {
+    pub window_ms: u64,
+    pub default_action: Action,
+    opened_at: u64,
+    moves: [Option<Action>; 2],
+}
+
+impl TurnTimer {
+    // Opens the first turn at `now_ms`, timed out players striking.
+    pub fn new(window_ms: u64, now_ms: u64) -> TurnTimer {
+        TurnTimer {
+            window_ms,
+            default_action: Action::Strike,
+            opened_at: now_ms,
+            moves: [None, None],
+        }
+    }
+
+    fn slot(side: Side) -> usize {
+        match side {
+            Side::Left => 0,
+            Side::Right => 1,
+            Side::Neither => panic!("Side::Neither can't submit a move"),
+        }
+    }
+
+    // Replaces anything `side` submitted earlier this turn.
+    pub fn submit(&mut self, side: Side, action: Action) {
+        self.moves[Self::slot(side)] = Some(action);
+    }
+
+    pub fn submitted(&self, side: Side) -> Option<Action> {
+        self.moves[Self::slot(side)]
+    }
+
+    pub fn timed_out(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.opened_at) >= self.window_ms
+    }
+
+    /*
+    Plays the turn on `game` once both moves are in or the window has run
+    out, returning the (left, right) pair played and opening the next turn
+    at `now_ms`. Returns None while still waiting. An illegal submission is
+    returned as the error with the turn left open, so it can be resubmitted.
+    */
+    pub fn poll(
+        &mut self,
+        game: &mut Game,
+        now_ms: u64,
+    ) -> Result<Option<(Action, Action)>, GameError> {
+        if self.moves.iter().any(Option::is_none) && !self.timed_out(now_ms) {
+            return Ok(None);
+        }
+        let fallback = |side: Side| {
+            if game.can_cast(side, self.default_action) {
+                self.default_action
+            } else {
+                Action::Concentrate
+            }
+        };
+        let left = self
+            .submitted(Side::Left)
+            .unwrap_or_else(|| fallback(Side::Left));
+        let right = self
+            .submitted(Side::Right)
+            .unwrap_or_else(|| fallback(Side::Right));
+        game.tick(left, right)?;
+        self.moves = [None, None];
+        self.opened_at = now_ms;
+        Ok(Some((left, right)))
+    }
+}
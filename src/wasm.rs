@@ -0,0 +1,33 @@
+use crate::{Action, Game, Side};
+use wasm_bindgen::prelude::*;
+
+/*
+A default-config duel as JavaScript sees it. Actions go in as their
+`Action::code` and sides as the strings serde writes for them ("Left",
+"Right"), and results come back as plain JS values from serde_wasm_bindgen.
+*/
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct WasmGame(Game);
+
+fn action(code: u8) -> Result<Action, JsError> {
+    Action::from_code(code).ok_or_else(|| JsError::new(&format!("unknown action code {}", code)))
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmGame {
+        WasmGame::default()
+    }
+
+    pub fn tick(&mut self, left: u8, right: u8) -> Result<(), JsError> {
+        Ok(self.0.tick(action(left)?, action(right)?)?)
+    }
+
+    // `Game::preview_all` for `side`, as an array of ActionPreview objects.
+    pub fn preview_all(&self, side: JsValue) -> Result<JsValue, JsError> {
+        let side: Side = serde_wasm_bindgen::from_value(side)?;
+        Ok(serde_wasm_bindgen::to_value(&self.0.preview_all(side))?)
+    }
+}
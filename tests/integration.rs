@@ -0,0 +1,2629 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use wizardfight_wasm::arena::{Arena, ArenaMove};
+use wizardfight_wasm::balance::{sweep, who_would_win, BalanceObjective, ConfigGrid, SweepEntry};
+use wizardfight_wasm::env::GameEnv;
+use wizardfight_wasm::rating::compute_ratings;
+use wizardfight_wasm::replay::{
+    excitement, find_seed, most_exciting, play_game, FrameState, Replay,
+};
+use wizardfight_wasm::rng::GameRng;
+use wizardfight_wasm::schema::interface_schema;
+use wizardfight_wasm::simulation::{
+    analyze_strategy, read_report, run_batch, run_matchup, simulate, simulate_to_file, write_report,
+};
+use wizardfight_wasm::strategy::{
+    strategy_by_name, AdaptiveStrategy, PressureStrategy, RandomStrategy, Strategy,
+    WeightedRandomStrategy,
+};
+use wizardfight_wasm::tournament::{
+    bracket, round_robin, Elimination, Entrant, MatchResult, TournamentResult,
+};
+use wizardfight_wasm::training::{export_training_data, train_weights, TrainingRow};
+use wizardfight_wasm::turn_timer::TurnTimer;
+use wizardfight_wasm::{
+    Action, ActionMix, ActionParser, EffectKind, Favorite, Game, GameConfig, GameError, GameEvent,
+    GameStatus, Lang, ParseActionError, PositionWeights, ReflectMode, Side, SimultaneousDeathRule,
+    Spell, SpellTable, StatusEffect, WinCondition, WinReason, WizardConfig,
+    BLOOD_MAGIC_HEALTH_COST,
+};
+
+fn low_health_game(health: u8) -> Game {
+    Game::with_config(GameConfig {
+        starting_health: health,
+        ..GameConfig::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn new_game_starts_at_full_health() {
+    let game = Game::new();
+    assert_eq!(game.wizard(Side::Left).health(), 25);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+    assert_eq!(game.wizard(Side::Left).mana(), 1);
+    assert_eq!(game.turn_count(), 0);
+    assert_eq!(game.status(), GameStatus::Ongoing);
+}
+
+#[test]
+fn scripted_turns_resolve_damage_and_mana() {
+    let mut game = Game::new();
+    game.tick(Action::Concentrate, Action::Strike).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 23);
+    assert_eq!(game.wizard(Side::Left).mana(), 6);
+    assert_eq!(game.wizard(Side::Right).mana(), 2);
+
+    game.tick(Action::LightningBolt, Action::ManaShield)
+        .unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+    assert_eq!(game.wizard(Side::Left).mana(), 5);
+    assert_eq!(game.wizard(Side::Right).mana(), 2);
+    assert_eq!(game.turn_count(), 2);
+}
+
+#[test]
+fn reflect_bounces_the_attack_back() {
+    let mut game = Game::new();
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    game.tick(Action::Fireball, Action::Reflect).unwrap();
+    // The 3 damage come back with 1 more.
+    assert_eq!(game.wizard(Side::Left).health(), 21);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+}
+
+#[test]
+fn illegal_move_is_rejected() {
+    let mut game = Game::new();
+    let err = game
+        .tick(Action::LightningBolt, Action::Strike)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        GameError::NotEnoughMana {
+            side: Side::Left,
+            action: Action::LightningBolt,
+            have: 1,
+            need: 2,
+        }
+    );
+    assert_eq!(game.turn_count(), 0);
+}
+
+#[test]
+fn lethal_hit_wins_the_game() {
+    let mut game = low_health_game(2);
+    game.tick(Action::Strike, Action::ManaShield).unwrap();
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    game.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert_eq!(game.status(), GameStatus::Winner(Side::Left));
+    assert_eq!(game.game_completed(), (true, Side::Left));
+    assert_eq!(game.result().unwrap().reason, WinReason::Lethal);
+}
+
+#[test]
+fn mutual_kill_is_a_draw() {
+    let mut game = low_health_game(2);
+    game.tick(Action::Strike, Action::Strike).unwrap();
+    assert_eq!(game.status(), GameStatus::Draw);
+    assert_eq!(game.result().unwrap().winner, None);
+}
+
+#[test]
+fn strategies_drive_a_game_to_the_end() {
+    let mut game = Game::with_config(GameConfig {
+        max_turns: Some(100),
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let mut left = AdaptiveStrategy::default();
+    let mut right = AdaptiveStrategy::new(0);
+    let mut rng = StdRng::seed_from_u64(0);
+    while game.status() == GameStatus::Ongoing {
+        let leftaction = left.choose(&game, Side::Left, &mut rng);
+        let rightaction = right.choose(&game, Side::Right, &mut rng);
+        game.tick(leftaction, rightaction).unwrap();
+    }
+    assert!(game.turn_count() <= 100);
+}
+
+#[test]
+fn zero_starting_health_is_rejected() {
+    let config = GameConfig {
+        starting_health: 0,
+        ..GameConfig::default()
+    };
+    assert_eq!(
+        Game::with_config(config),
+        Err(GameError::InvalidConfig("starting_health must be above 0"))
+    );
+}
+
+#[test]
+fn seeded_simulation_is_reproducible() {
+    let config = GameConfig::default();
+    let report = simulate(&config, 50, 42).unwrap();
+    assert_eq!(report.games, 50);
+    assert_eq!(report.left_wins + report.right_wins + report.draws, 50);
+    assert_eq!(report.game_lengths.values().sum::<u32>(), 50);
+    assert_eq!(report, simulate(&config, 50, 42).unwrap());
+}
+
+#[test]
+fn shared_team_mana_funds_a_teammates_spell() {
+    let idle = |action| ArenaMove { action, target: 0 };
+    let opening = [
+        idle(Action::Concentrate),
+        idle(Action::ManaShield),
+        idle(Action::ManaShield),
+        idle(Action::ManaShield),
+    ];
+    // Wizard 1 is down to 1 mana and can only afford the bolt with wizard 0's help.
+    let bolt = [
+        idle(Action::Concentrate),
+        ArenaMove {
+            action: Action::LightningBolt,
+            target: 2,
+        },
+        idle(Action::Concentrate),
+        idle(Action::Concentrate),
+    ];
+
+    let mut solo = Arena::with_teams(vec![0, 0, 1, 1], GameConfig::default()).unwrap();
+    solo.tick(&opening).unwrap();
+    assert!(solo.tick(&bolt).is_err());
+
+    let config = GameConfig {
+        shared_team_mana: true,
+        ..GameConfig::default()
+    };
+    let mut arena = Arena::with_teams(vec![0, 0, 1, 1], config).unwrap();
+    arena.tick(&opening).unwrap();
+    assert_eq!(arena.team_mana(0), 7);
+    arena.tick(&bolt).unwrap();
+    assert_eq!(arena.wizard(2).health(), 20);
+    assert_eq!(arena.wizard(1).mana(), 1);
+    assert_eq!(arena.wizard(0).mana(), 10);
+}
+
+#[test]
+fn arena_reflects_bounce_once_without_chaining() {
+    let config = GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    };
+    let mut arena = Arena::with_config(3, config).unwrap();
+    // 0's bolt bounces off 1's Reflect onto 0, and 2's Fireball at 0 lands on top of it.
+    arena
+        .tick(&[
+            ArenaMove {
+                action: Action::LightningBolt,
+                target: 1,
+            },
+            ArenaMove {
+                action: Action::Reflect,
+                target: 0,
+            },
+            ArenaMove {
+                action: Action::Fireball,
+                target: 0,
+            },
+        ])
+        .unwrap();
+    let health: Vec<u8> = (0..3).map(|index| arena.wizard(index).health()).collect();
+    assert_eq!(health, [16, 25, 25]);
+}
+
+#[test]
+fn saved_reports_load_back_unchanged() {
+    let path = std::env::temp_dir().join("wizardfight_saved_report.json");
+    let config = GameConfig::default();
+    let report = simulate_to_file(&config, 10, 3, &path).unwrap();
+    assert_eq!(read_report(&path).unwrap(), report);
+
+    let other = simulate(&config, 5, 4).unwrap();
+    write_report(&other, &path).unwrap();
+    let loaded = read_report(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), other);
+}
+
+#[test]
+fn report_echoes_seed_and_config_hash() {
+    let config = GameConfig::default();
+    let report = simulate(&config, 10, 7).unwrap();
+    assert_eq!(report.seed, 7);
+    assert_eq!(report.config_hash, config.config_hash());
+    assert_eq!(report, simulate(&config, 10, report.seed).unwrap());
+
+    let tweaked = GameConfig {
+        mana_per_turn: 2,
+        ..GameConfig::default()
+    };
+    assert_ne!(tweaked.config_hash(), config.config_hash());
+}
+
+#[test]
+fn games_without_passive_mana_still_end_with_more_concentrating() {
+    let concentrate_share = |mana_per_turn| {
+        let config = GameConfig {
+            mana_per_turn,
+            ..GameConfig::default()
+        };
+        let report = simulate(&config, 50, 11).unwrap();
+        assert_eq!(report.left_wins + report.right_wins + report.draws, 50);
+        let played: u64 = report.action_counts.values().sum();
+        report.action_counts[&Action::Concentrate] as f64 / played as f64
+    };
+    assert!(concentrate_share(0) > concentrate_share(1));
+}
+
+#[test]
+fn concentrate_is_always_available() {
+    let starved = Game::with_config(GameConfig {
+        starting_mana: 0,
+        mana_per_turn: 0,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    assert!(starved
+        .available_actions(Side::Left)
+        .contains(&Action::Concentrate));
+
+    let capped = Game::with_config(GameConfig {
+        starting_mana: 5,
+        max_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    assert!(capped
+        .available_actions(Side::Left)
+        .contains(&Action::Concentrate));
+
+    let mut spells = SpellTable::default();
+    spells.set(
+        Action::Concentrate,
+        Spell {
+            damage: 0,
+            mana_cost: 3,
+        },
+    );
+    let misconfigured = Game::with_config(GameConfig {
+        spells,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    assert!(misconfigured.can_cast(Side::Left, Action::Concentrate));
+}
+
+#[test]
+fn damage_variance_rolls_around_the_base() {
+    let mut exact = Game::new();
+    exact.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert_eq!(exact.wizard(Side::Right).health(), 23);
+
+    let config = GameConfig {
+        damage_variance: 1,
+        ..GameConfig::default()
+    };
+    let mut total = 0;
+    let rolls = 2000;
+    for seed in 0..rolls {
+        let mut game = Game::with_seed(config.clone(), seed).unwrap();
+        game.tick(Action::Strike, Action::Concentrate).unwrap();
+        let damage = 25 - game.wizard(Side::Right).health() as u32;
+        assert!((1..=3).contains(&damage));
+        total += damage;
+    }
+    let mean = total as f64 / rolls as f64;
+    assert!((mean - 2.0).abs() < 0.1, "mean damage was {}", mean);
+}
+
+#[test]
+fn recent_actions_returns_the_latest_moves_in_order() {
+    let mut game = Game::new();
+    let script = [
+        (Action::Strike, Action::Concentrate),
+        (Action::Strike, Action::Fireball),
+        (Action::Concentrate, Action::Strike),
+        (Action::Fireball, Action::ManaShield),
+    ];
+    for (left, right) in script {
+        game.tick(left, right).unwrap();
+    }
+    assert_eq!(
+        game.recent_actions(Side::Right, 3),
+        vec![Action::Fireball, Action::Strike, Action::ManaShield]
+    );
+    assert_eq!(game.recent_actions(Side::Left, 10).len(), 4);
+}
+
+#[test]
+fn surrender_ends_the_game_before_the_turn_resolves() {
+    let mut game = Game::new();
+    game.tick(Action::Surrender, Action::Strike).unwrap();
+    assert_eq!(game.status(), GameStatus::Winner(Side::Right));
+    assert_eq!(game.result().unwrap().reason, WinReason::Surrender);
+    assert_eq!(game.wizard(Side::Left).health(), 25);
+    assert_eq!(game.turn_count(), 1);
+}
+
+#[test]
+fn both_wizards_surrendering_is_a_draw() {
+    let mut game = Game::new();
+    game.tick(Action::Surrender, Action::Surrender).unwrap();
+    assert_eq!(game.status(), GameStatus::Draw);
+    assert_eq!(game.result().unwrap().reason, WinReason::Surrender);
+}
+
+#[test]
+fn simultaneous_death_rules_decide_a_mutual_lightning_bolt() {
+    let expected = [
+        (SimultaneousDeathRule::Draw, None),
+        (SimultaneousDeathRule::PenalizeOverkill, Some(Side::Right)),
+        (SimultaneousDeathRule::OverkillWins, Some(Side::Left)),
+    ];
+    for (rule, winner) in expected {
+        let mut game = Game::with_config(GameConfig {
+            starting_health: 5,
+            starting_mana: 4,
+            simultaneous_death: rule,
+            ..GameConfig::default()
+        })
+        .unwrap();
+        game.tick(Action::Strike, Action::Concentrate).unwrap();
+        // Right is down to 3 HP, so Left's bolt overkills by 2 and Right's by 0.
+        game.tick(Action::LightningBolt, Action::LightningBolt)
+            .unwrap();
+        let result = game.result().unwrap();
+        assert_eq!(result.winner, winner, "{:?}", rule);
+        assert_eq!(result.reason, WinReason::Lethal);
+    }
+}
+
+#[test]
+fn consecutive_concentrates_give_diminishing_mana() {
+    let mut game = Game::with_config(GameConfig {
+        channeling_limit: true,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let mut gains = Vec::new();
+    for left in [
+        Action::Concentrate,
+        Action::Concentrate,
+        Action::Strike,
+        Action::Concentrate,
+    ] {
+        let before = game.wizard(Side::Left).mana();
+        game.tick(left, Action::Concentrate).unwrap();
+        gains.push(game.wizard(Side::Left).mana() - before);
+    }
+    // Concentrate plus 1 passive mana, halved when repeated and reset by the Strike.
+    assert_eq!(gains, vec![5, 3, 1, 5]);
+    assert_eq!(
+        game.wizard(Side::Left).last_action(),
+        Some(Action::Concentrate)
+    );
+}
+
+#[test]
+fn all_actions_lists_every_variant_once() {
+    let all = Action::all();
+    let unique: std::collections::HashSet<_> = all.iter().collect();
+    assert_eq!(all.len(), 18);
+    assert_eq!(unique.len(), all.len());
+    assert_eq!(all.last(), Some(&Action::Surrender));
+}
+
+#[test]
+fn status_effects_decay_and_stack() {
+    let mut game = Game::new();
+    game.apply_effect(Side::Left, StatusEffect::new(EffectKind::Poison, 2, -2));
+    game.apply_effect(
+        Side::Left,
+        StatusEffect::new(EffectKind::Regeneration, 3, 1),
+    );
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 24);
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 23);
+    assert!(!game.wizard(Side::Left).has_effect(EffectKind::Poison));
+    assert!(game.wizard(Side::Left).has_effect(EffectKind::Regeneration));
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 24);
+    assert!(game.wizard(Side::Left).effects().is_empty());
+}
+
+#[test]
+fn regeneration_never_heals_above_starting_health() {
+    let mut game = Game::new();
+    game.apply_effect(
+        Side::Right,
+        StatusEffect::new(EffectKind::Regeneration, 5, 3),
+    );
+    game.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+}
+
+#[test]
+fn solve_favours_the_healthier_wizard() {
+    let mut game = Game::new();
+    game.apply_effect(Side::Right, StatusEffect::new(EffectKind::Poison, 1, -20));
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    assert_eq!(game.solve(2), Favorite::Left);
+}
+
+#[test]
+fn solve_calls_a_mirror_position_balanced() {
+    assert_eq!(Game::new().solve(2), Favorite::Balanced);
+}
+
+#[test]
+fn casting_without_mana_reports_side_have_and_need() {
+    let config = GameConfig {
+        starting_mana: 0,
+        ..GameConfig::default()
+    };
+    let costs = [
+        (Action::Fireball, 1),
+        (Action::LightningBolt, 2),
+        (Action::ManaShield, 1),
+        (Action::Reflect, 2),
+    ];
+    for (action, need) in costs {
+        for side in [Side::Left, Side::Right] {
+            let mut game = Game::with_config(config.clone()).unwrap();
+            let (left, right) = match side {
+                Side::Left => (action, Action::Concentrate),
+                _ => (Action::Concentrate, action),
+            };
+            assert_eq!(
+                game.tick(left, right),
+                Err(GameError::NotEnoughMana {
+                    side,
+                    action,
+                    have: 0,
+                    need,
+                })
+            );
+        }
+    }
+}
+
+#[test]
+fn rejected_move_leaves_the_game_untouched() {
+    let mut game = Game::new();
+    game.tick(Action::Strike, Action::Fireball).unwrap();
+    let health = (
+        game.wizard(Side::Left).health(),
+        game.wizard(Side::Right).health(),
+    );
+    let mana = (
+        game.wizard(Side::Left).mana(),
+        game.wizard(Side::Right).mana(),
+    );
+
+    // Right has 1 mana, so the bolt is refused after Left's Strike passed the check.
+    assert!(game.tick(Action::Strike, Action::LightningBolt).is_err());
+    assert_eq!(
+        (
+            game.wizard(Side::Left).health(),
+            game.wizard(Side::Right).health()
+        ),
+        health
+    );
+    assert_eq!(
+        (
+            game.wizard(Side::Left).mana(),
+            game.wizard(Side::Right).mana()
+        ),
+        mana
+    );
+    assert_eq!(game.turn_count(), 1);
+    assert_eq!(game.history().len(), 1);
+}
+
+fn defender_config() -> GameConfig {
+    let spellbook = [
+        Action::ManaShield,
+        Action::Reflect,
+        Action::Concentrate,
+        Action::Strike,
+    ];
+    GameConfig {
+        max_turns: Some(100),
+        left_wizard: WizardConfig {
+            spellbook: spellbook.into_iter().collect(),
+            ..WizardConfig::default()
+        },
+        ..GameConfig::default()
+    }
+}
+
+#[test]
+fn spellbook_forbids_unlisted_actions() {
+    let mut game = Game::with_config(defender_config()).unwrap();
+    for _ in 0..3 {
+        game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    }
+    assert!(!game.can_cast(Side::Left, Action::LightningBolt));
+    assert!(!game
+        .available_actions(Side::Left)
+        .contains(&Action::LightningBolt));
+    assert_eq!(
+        game.tick(Action::LightningBolt, Action::Strike),
+        Err(GameError::NotInSpellbook {
+            side: Side::Left,
+            action: Action::LightningBolt,
+        })
+    );
+    assert!(game.can_cast(Side::Right, Action::LightningBolt));
+}
+
+#[test]
+fn restricted_spellbook_wins_less_often() {
+    let (mut defender_wins, mut full_wins) = (0, 0);
+    for threshold in 0..8 {
+        let mut game = Game::with_config(defender_config()).unwrap();
+        let mut left = AdaptiveStrategy::new(threshold);
+        let mut right = AdaptiveStrategy::new(threshold);
+        let mut rng = StdRng::seed_from_u64(0);
+        while game.result().is_none() {
+            let left_action = left.choose(&game, Side::Left, &mut rng);
+            let right_action = right.choose(&game, Side::Right, &mut rng);
+            game.tick(left_action, right_action).unwrap();
+        }
+        assert!(game
+            .history()
+            .iter()
+            .all(|&(left_action, _)| left_action != Action::LightningBolt));
+        match game.result().unwrap().winner {
+            Some(Side::Left) => defender_wins += 1,
+            Some(_) => full_wins += 1,
+            None => (),
+        }
+    }
+    assert!(defender_wins < full_wins);
+}
+
+#[test]
+fn pure_mixes_give_a_single_certain_outcome() {
+    let game = Game::new();
+    let strike = ActionMix::from([(Action::Strike, 1.0)]);
+    let concentrate = ActionMix::from([(Action::Concentrate, 1.0)]);
+    let outcomes = game.outcome_distribution(&strike, &concentrate);
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].0.right.health, 23);
+    assert_eq!(outcomes[0].1, 1.0);
+}
+
+#[test]
+fn even_mix_splits_the_outcomes_in_half() {
+    let game = Game::new();
+    let left = ActionMix::from([(Action::Strike, 1.0), (Action::Fireball, 1.0)]);
+    let right = ActionMix::from([(Action::Concentrate, 1.0)]);
+    let outcomes = game.outcome_distribution(&left, &right);
+    let mut healths: Vec<(u8, f64)> = outcomes
+        .iter()
+        .map(|(snapshot, odds)| (snapshot.right.health, *odds))
+        .collect();
+    healths.sort_by_key(|&(health, _)| health);
+    assert_eq!(healths, vec![(22, 0.5), (23, 0.5)]);
+    let total: f64 = outcomes.iter().map(|(_, odds)| odds).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn training_export_writes_a_row_per_reference_move() {
+    let path = std::env::temp_dir().join("wizardfight_training_export.json");
+    let games = 20;
+    let written = export_training_data(&mut AdaptiveStrategy::default(), games, 7, &path).unwrap();
+    let rows: Vec<TrainingRow> =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(rows.len(), written);
+    // One row per turn, and every game lasts between 1 and 100 turns.
+    assert!(written >= games as usize && written <= games as usize * 100);
+    assert!(rows
+        .iter()
+        .all(|row| row.turn < 100 && row.own_health <= 25 && row.opponent_health <= 25));
+    assert_eq!(rows[0].turn, 0);
+}
+
+fn reflect_game(reflect_mode: ReflectMode) -> Game {
+    Game::with_config(GameConfig {
+        starting_mana: 5,
+        reflect_mode,
+        ..GameConfig::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn full_reflect_bounces_the_whole_attack() {
+    let mut game = reflect_game(ReflectMode::Full);
+    game.tick(Action::LightningBolt, Action::Reflect).unwrap();
+    // All 5 damage and the reflect's 1 on top.
+    assert_eq!(game.wizard(Side::Left).health(), 19);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+}
+
+#[test]
+fn halving_reflect_bounces_half_the_damage() {
+    let mut game = reflect_game(ReflectMode::HalveAndBounce);
+    game.tick(Action::LightningBolt, Action::Reflect).unwrap();
+    // Half of 5 rounded down, plus 1.
+    assert_eq!(game.wizard(Side::Left).health(), 22);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+}
+
+#[test]
+fn attacks_only_reflect_lets_mana_burn_through() {
+    let mut game = reflect_game(ReflectMode::AttacksOnly);
+    game.tick(Action::ManaBurn, Action::Reflect).unwrap();
+    // Left paid 2, Right paid 2 and lost 3, both got 1 back.
+    assert_eq!(game.wizard(Side::Left).mana(), 4);
+    assert_eq!(game.wizard(Side::Right).mana(), 1);
+}
+
+#[test]
+fn fingerprint_matches_for_identical_states_only() {
+    let mut first = Game::new();
+    let mut second = Game::new();
+    first.tick(Action::Strike, Action::Concentrate).unwrap();
+    second.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert_eq!(first.fingerprint(), second.fingerprint());
+
+    let mut poisoned = first.clone();
+    poisoned.apply_effect(Side::Left, StatusEffect::new(EffectKind::Poison, 2, -1));
+    assert_ne!(first.fingerprint(), poisoned.fingerprint());
+    second
+        .tick(Action::Concentrate, Action::Concentrate)
+        .unwrap();
+    assert_ne!(first.fingerprint(), second.fingerprint());
+}
+
+#[test]
+fn charges_cap_out_and_release_for_scaled_damage() {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    for _ in 0..4 {
+        game.tick(Action::Charge, Action::Concentrate).unwrap();
+    }
+    assert_eq!(game.wizard(Side::Left).charges(), 3);
+    game.tick(Action::Release, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 19);
+    assert_eq!(game.wizard(Side::Left).charges(), 0);
+
+    // Nothing left to release, the turn is simply wasted.
+    game.tick(Action::Release, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 19);
+}
+
+#[test]
+fn mana_shield_soaks_a_release_and_its_charges() {
+    let mut game = Game::new();
+    game.tick(Action::Charge, Action::Concentrate).unwrap();
+    game.tick(Action::Charge, Action::Concentrate).unwrap();
+    game.tick(Action::Release, Action::ManaShield).unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+    assert_eq!(game.wizard(Side::Left).charges(), 0);
+}
+
+#[test]
+fn named_matchup_produces_a_full_report() {
+    let config = GameConfig::default();
+    let report = run_matchup(&config, "greedy", "random", 50, 3).unwrap();
+    assert_eq!(report.games, 50);
+    assert_eq!(report.left_wins + report.right_wins + report.draws, 50);
+    assert_eq!(report.game_lengths.values().sum::<u32>(), 50);
+    assert!(run_matchup(&config, "greedy", "nobody", 1, 3).is_err());
+}
+
+#[test]
+fn first_turn_attacks_can_be_disabled() {
+    let config = GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    };
+    let mut open = Game::with_config(config.clone()).unwrap();
+    open.tick(Action::LightningBolt, Action::Concentrate)
+        .unwrap();
+    assert_eq!(open.wizard(Side::Right).health(), 20);
+
+    let mut guarded = Game::with_config(GameConfig {
+        first_turn_attacks_disabled: true,
+        ..config
+    })
+    .unwrap();
+    assert_eq!(
+        guarded.tick(Action::Concentrate, Action::LightningBolt),
+        Err(GameError::FirstTurnAttack {
+            side: Side::Right,
+            action: Action::LightningBolt,
+        })
+    );
+    guarded
+        .tick(Action::ManaShield, Action::Concentrate)
+        .unwrap();
+    guarded
+        .tick(Action::LightningBolt, Action::Concentrate)
+        .unwrap();
+    assert_eq!(guarded.wizard(Side::Right).health(), 20);
+}
+
+#[test]
+fn trained_weights_beat_untrained_ones() {
+    let trained = train_weights(4, 6, 1);
+    assert!(!trained.contains_key(&Action::Surrender));
+    assert!(!trained.contains_key(&Action::Combo));
+    let untrained = trained.keys().map(|&action| (action, 1.0)).collect();
+
+    let config = GameConfig {
+        max_turns: Some(100),
+        ..GameConfig::default()
+    };
+    let wins = |weights: &HashMap<Action, f64>| {
+        let mut wins = 0;
+        for threshold in [0, 5, u8::MAX] {
+            let mut candidate = WeightedRandomStrategy::new(weights.clone());
+            let mut opponent = AdaptiveStrategy::new(threshold);
+            let results = run_batch(&config, &mut candidate, &mut opponent, 50, 3).unwrap();
+            wins += results
+                .iter()
+                .filter(|result| result.winner == Some(Side::Left))
+                .count();
+        }
+        wins
+    };
+    assert!(wins(&trained) > wins(&untrained));
+}
+
+#[test]
+fn tournament_games_roll_their_own_variance() {
+    let config = GameConfig {
+        damage_variance: 2,
+        max_turns: Some(100),
+        ..GameConfig::default()
+    };
+    let mut entrants = vec![
+        Entrant::new("adaptive", AdaptiveStrategy::default()),
+        Entrant::new("mirror", AdaptiveStrategy::default()),
+    ];
+    // The same deterministic strategy on both sides, so only the rolls tell games apart.
+    let record = &round_robin(&mut entrants, 20, &config, 3).unwrap().matches[0];
+    assert!(
+        record.left_wins > 0 && record.right_wins > 0,
+        "{:?}",
+        record
+    );
+}
+
+#[test]
+fn seeded_tournaments_with_random_entrants_repeat_exactly() {
+    let config = GameConfig {
+        max_turns: Some(100),
+        ..GameConfig::default()
+    };
+    let tournament = || {
+        let mut entrants = vec![
+            Entrant::new("random", RandomStrategy),
+            Entrant::new("also random", RandomStrategy),
+            Entrant::new("adaptive", AdaptiveStrategy::default()),
+        ];
+        round_robin(&mut entrants, 20, &config, 9).unwrap()
+    };
+    let first = tournament();
+    assert_eq!(first.matches.len(), 3);
+    assert_eq!(first, tournament());
+}
+
+#[test]
+fn a_strategy_that_sweeps_rates_highest() {
+    let record = |left: &str, right: &str, left_wins, right_wins, draws| MatchResult {
+        left: left.to_string(),
+        right: right.to_string(),
+        left_wins,
+        right_wins,
+        draws,
+    };
+    let results = TournamentResult {
+        matches: vec![
+            record("sweeper", "adaptive", 10, 0, 0),
+            record("sweeper", "random", 10, 0, 0),
+            record("adaptive", "random", 4, 4, 2),
+        ],
+    };
+    let ratings = compute_ratings(&results);
+    assert!(ratings["sweeper"] > ratings["adaptive"]);
+    assert!(ratings["sweeper"] > ratings["random"]);
+}
+
+#[test]
+fn encoding_round_trips_representative_states() {
+    let config = GameConfig {
+        last_stand: true,
+        ..GameConfig::default()
+    };
+    let mut states = vec![Game::with_config(config.clone()).unwrap()];
+    let mut game = Game::with_config(config.clone()).unwrap();
+    game.tick(Action::Fireball, Action::Strike).unwrap();
+    states.push(game.clone());
+    game.apply_effect(Side::Right, StatusEffect::new(EffectKind::Poison, 1, -20));
+    game.tick(Action::Strike, Action::Concentrate).unwrap();
+    states.push(game.clone());
+    let mut surrendered = game.clone();
+    surrendered
+        .tick(Action::Concentrate, Action::Surrender)
+        .unwrap();
+    states.push(surrendered);
+
+    for state in states {
+        let code = state.encode().unwrap();
+        let decoded = Game::decode(code, config.clone()).unwrap();
+        assert_eq!(decoded.encode().unwrap(), code);
+        for side in [Side::Left, Side::Right] {
+            let (before, after) = (state.wizard(side), decoded.wizard(side));
+            assert_eq!(before.health(), after.health());
+            assert_eq!(before.mana(), after.mana());
+            assert_eq!(before.last_stand_used(), after.last_stand_used());
+            assert_eq!(before.surrendered(), after.surrendered());
+        }
+    }
+}
+
+#[test]
+fn decoding_rejects_positions_the_config_cant_reach() {
+    assert_eq!(
+        Game::decode(63 | 10 << 6 | 3 << 12 | 3 << 16, GameConfig::default()),
+        Err(GameError::DecodedOutOfRange {
+            field: "left health",
+            value: 63,
+            max: 25,
+        })
+    );
+    let capped = GameConfig {
+        max_mana: 5,
+        ..GameConfig::default()
+    };
+    assert_eq!(
+        Game::decode(20 | 20 << 6 | 5 << 12 | 9 << 16, capped),
+        Err(GameError::DecodedOutOfRange {
+            field: "right mana",
+            value: 9,
+            max: 5,
+        })
+    );
+}
+
+#[test]
+fn encoding_rejects_health_above_six_bits() {
+    let game = low_health_game(64);
+    assert_eq!(
+        game.encode(),
+        Err(GameError::EncodingOverflow {
+            field: "left health",
+            value: 64,
+        })
+    );
+}
+
+#[test]
+fn action_matrix_covers_every_pairing() {
+    let game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let actions = Game::matrix_actions();
+    let health_only = PositionWeights { health: 1, mana: 0 };
+    let matrix = game.action_matrix_with(&health_only);
+    assert_eq!(matrix.len(), actions.len());
+    assert!(matrix.iter().all(|row| row.len() == actions.len()));
+
+    let index = |action| actions.iter().position(|&a| a == action).unwrap();
+    assert_eq!(
+        matrix[index(Action::Strike)][index(Action::ManaShield)],
+        Some(0)
+    );
+    assert_eq!(
+        matrix[index(Action::Strike)][index(Action::Concentrate)],
+        Some(2)
+    );
+}
+
+#[test]
+fn teleport_dodges_attacks_without_reflecting() {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    game.tick(Action::LightningBolt, Action::Teleport).unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+    assert_eq!(game.wizard(Side::Left).health(), 25);
+}
+
+#[test]
+fn teleport_cannot_be_cast_two_turns_running() {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 10,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    game.tick(Action::Teleport, Action::Concentrate).unwrap();
+    assert!(!game.can_cast(Side::Left, Action::Teleport));
+    assert_eq!(
+        game.tick(Action::Teleport, Action::Concentrate),
+        Err(GameError::OnCooldown {
+            side: Side::Left,
+            action: Action::Teleport,
+        })
+    );
+    game.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert!(game.can_cast(Side::Left, Action::Teleport));
+}
+
+#[test]
+fn stepping_two_strategies_reaches_a_result() {
+    let mut game = Game::new();
+    let mut left = AdaptiveStrategy::default();
+    let weights = Action::all().iter().map(|&action| (action, 1.0)).collect();
+    let mut right = WeightedRandomStrategy::new(weights);
+    let (mut left_rng, mut right_rng) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(2));
+    let mut status = None;
+    for _ in 0..1000 {
+        status = game
+            .step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+            .unwrap();
+        if status.is_some() {
+            break;
+        }
+    }
+    assert_eq!(status, Some(game.status()));
+    assert!(matches!(status, Some(GameStatus::Winner(_))));
+}
+
+fn reactive_shield_game(starting_mana: u8) -> Game {
+    Game::with_config(GameConfig {
+        starting_mana,
+        reactive_shield: true,
+        ..GameConfig::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn reactive_shield_charges_little_for_a_strike() {
+    let mut game = reactive_shield_game(5);
+    game.tick(Action::Strike, Action::ManaShield).unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+    // 1 for the shield, 1 for the 2 damage absorbed, 1 back passively.
+    assert_eq!(game.wizard(Side::Right).mana(), 4);
+}
+
+#[test]
+fn reactive_shield_lets_an_unaffordable_bolt_partly_through() {
+    let mut rich = reactive_shield_game(5);
+    rich.tick(Action::LightningBolt, Action::ManaShield)
+        .unwrap();
+    assert_eq!(rich.wizard(Side::Right).health(), 25);
+    assert_eq!(rich.wizard(Side::Right).mana(), 3);
+
+    // One mana left after the shield only absorbs 2 of the 5 damage.
+    let mut poor = reactive_shield_game(2);
+    poor.tick(Action::LightningBolt, Action::ManaShield)
+        .unwrap();
+    assert_eq!(poor.wizard(Side::Right).health(), 22);
+    assert_eq!(poor.wizard(Side::Right).mana(), 1);
+}
+
+#[test]
+fn fireball_no_better_than_a_free_strike_is_dominated() {
+    let mut spells = SpellTable::default();
+    spells.set(
+        Action::Fireball,
+        Spell {
+            damage: 2,
+            mana_cost: 1,
+        },
+    );
+    let game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        spells,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let dominated = game.dominated_actions();
+    let fireball = dominated
+        .iter()
+        .find(|dominated| dominated.action == Action::Fireball)
+        .unwrap();
+    assert_eq!(fireball.dominated_by, Action::Strike);
+    assert!(!fireball.explanation.is_empty());
+    assert!(dominated
+        .iter()
+        .all(|dominated| dominated.action != Action::Strike));
+}
+
+#[test]
+fn queued_combo_plays_in_order() {
+    let mut game = Game::new();
+    game.queue_combo(Side::Left, &[Action::Concentrate, Action::LightningBolt]);
+    game.tick(Action::Combo, Action::Concentrate).unwrap();
+    game.tick(Action::Combo, Action::Concentrate).unwrap();
+    assert_eq!(
+        game.recent_actions(Side::Left, 2),
+        vec![Action::Concentrate, Action::LightningBolt]
+    );
+    assert_eq!(game.wizard(Side::Right).health(), 20);
+    assert_eq!(
+        game.tick(Action::Combo, Action::Concentrate),
+        Err(GameError::EmptyCombo { side: Side::Left })
+    );
+}
+
+#[test]
+fn getting_hit_interrupts_a_combo() {
+    let mut game = Game::new();
+    game.queue_combo(Side::Left, &[Action::Concentrate, Action::LightningBolt]);
+    game.tick(Action::Combo, Action::Strike).unwrap();
+    assert!(game.wizard(Side::Left).queue().is_empty());
+    assert!(!game.can_cast(Side::Left, Action::Combo));
+}
+
+#[test]
+fn blood_magic_in_a_combo_keeps_the_rest_queued() {
+    let mut game = Game::new();
+    let health = game.wizard(Side::Left).health();
+    game.queue_combo(Side::Left, &[Action::BloodMagic, Action::Concentrate]);
+    game.tick(Action::Combo, Action::Concentrate).unwrap();
+    assert_eq!(
+        game.wizard(Side::Left).health(),
+        health - BLOOD_MAGIC_HEALTH_COST
+    );
+    assert_eq!(
+        game.wizard(Side::Left).queue().iter().collect::<Vec<_>>(),
+        vec![&Action::Concentrate]
+    );
+    game.tick(Action::Combo, Action::Concentrate).unwrap();
+    assert_eq!(
+        game.recent_actions(Side::Left, 2),
+        vec![Action::BloodMagic, Action::Concentrate]
+    );
+}
+
+#[test]
+fn mana_decay_makes_hoarding_plateau() {
+    let hoard = |mana_decay| {
+        let mut game = Game::with_config(GameConfig {
+            max_mana: 60,
+            mana_decay,
+            mana_decay_threshold: 10,
+            ..GameConfig::default()
+        })
+        .unwrap();
+        let mut peak = 0;
+        for _ in 0..50 {
+            game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+            peak = peak.max(game.wizard(Side::Left).mana());
+        }
+        peak
+    };
+    assert_eq!(hoard(0), 60);
+    assert!(hoard(6) <= 15, "mana peaked at {}", hoard(6));
+}
+
+#[test]
+fn play_game_bundles_result_and_replay() {
+    let config = GameConfig {
+        max_turns: Some(200),
+        ..GameConfig::default()
+    };
+    let record = play_game(&mut RandomStrategy, &mut RandomStrategy, config.clone(), 5).unwrap();
+    assert!(record.result.turns > 0);
+    assert_eq!(record.replay.turns.len() as u32, record.result.turns);
+    record.replay.verify().unwrap();
+
+    let again = play_game(&mut RandomStrategy, &mut RandomStrategy, config, 5).unwrap();
+    assert_eq!(again.result, record.result);
+}
+
+#[test]
+fn verify_catches_a_tampered_turn() {
+    let config = GameConfig {
+        max_turns: Some(200),
+        ..GameConfig::default()
+    };
+    let mut replay = play_game(&mut RandomStrategy, &mut RandomStrategy, config, 5)
+        .unwrap()
+        .replay;
+    let turn = replay.turns.len() / 2;
+    replay.turns[turn].left.mana = replay.turns[turn].left.mana.wrapping_add(1);
+    let err = replay.verify().unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with(&format!("turn {} diverged", turn + 1)),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn balance_sweep_picks_the_best_table_per_objective() {
+    let candidates: Vec<SpellTable> = [1, 4, 8]
+        .into_iter()
+        .map(|damage| {
+            let mut spells = SpellTable::default();
+            spells.set(
+                Action::Strike,
+                Spell {
+                    damage,
+                    mana_cost: 0,
+                },
+            );
+            spells
+        })
+        .collect();
+    let result = sweep(&GameConfig::default(), &candidates, 2000, 1).unwrap();
+    assert_eq!(result.entries.len(), 3);
+
+    // Each objective picks whichever table scored lowest on it.
+    let picks_the_argmin = |objective, score: fn(&SweepEntry) -> f64| {
+        let best = result.best(objective).unwrap();
+        let picked = result
+            .entries
+            .iter()
+            .find(|entry| &entry.spells == best)
+            .unwrap();
+        assert!(result
+            .entries
+            .iter()
+            .all(|entry| score(picked) <= score(entry)));
+    };
+    picks_the_argmin(BalanceObjective::FewestTies, SweepEntry::tie_rate);
+    picks_the_argmin(BalanceObjective::Fairness, SweepEntry::unfairness);
+}
+
+#[test]
+fn identically_played_games_compare_equal() {
+    let play = |right| {
+        let mut game = Game::new();
+        game.tick(Action::Strike, Action::Concentrate).unwrap();
+        game.tick(Action::Fireball, right).unwrap();
+        game
+    };
+    let (first, second) = (play(Action::Strike), play(Action::Strike));
+    assert_eq!(first, second);
+    assert_eq!(first.left(), second.left());
+
+    let divergent = play(Action::ManaShield);
+    assert_ne!(first, divergent);
+    assert_ne!(first.right(), divergent.right());
+    assert_eq!(first.left().health(), 23);
+}
+
+fn recorded_game(health: u8, script: &[(Action, Action)]) -> Replay {
+    let config = GameConfig {
+        starting_health: health,
+        ..GameConfig::default()
+    };
+    let mut game = Game::with_config(config.clone()).unwrap();
+    let mut replay = Replay::new(config);
+    for &(left, right) in script {
+        game.tick(left, right).unwrap();
+        replay.record(left, right, &game);
+    }
+    assert!(game.result().is_some());
+    replay
+}
+
+#[test]
+fn one_hp_comeback_is_more_exciting_than_a_blowout() {
+    let blowout = recorded_game(9, &[(Action::Strike, Action::Concentrate); 5]);
+    let mut script = vec![(Action::Concentrate, Action::Strike); 4];
+    script.extend([(Action::LightningBolt, Action::Concentrate); 2]);
+    let comeback = recorded_game(9, &script);
+
+    assert!(excitement(&comeback) > excitement(&blowout));
+    assert_eq!(most_exciting(&[blowout, comeback], 1), vec![1]);
+}
+
+#[test]
+fn default_spells_match_their_action_kind() {
+    let spells = SpellTable::default();
+    for &action in Action::all() {
+        let spell = spells.get(action);
+        if action.is_attack() {
+            assert!(spell.damage > 0, "{:?} should deal damage", action);
+        }
+        if action.is_defensive() {
+            assert_eq!(spell.damage, 0, "{:?} shouldn't deal damage", action);
+        }
+        if spell.mana_cost < 0 {
+            assert!(
+                matches!(action, Action::Concentrate | Action::BloodMagic),
+                "{:?} shouldn't restore mana",
+                action
+            );
+        }
+    }
+    assert_eq!(spells.validate(), Ok(()));
+}
+
+#[test]
+fn spell_table_validation_rejects_a_harmless_attack() {
+    let mut spells = SpellTable::default();
+    spells.set(
+        Action::Fireball,
+        Spell {
+            damage: 0,
+            mana_cost: 1,
+        },
+    );
+    assert!(spells.validate().is_err());
+    let config = GameConfig {
+        spells,
+        ..GameConfig::default()
+    };
+    assert!(Game::with_config(config).is_err());
+}
+
+#[test]
+fn schema_lists_every_action_with_a_unique_code() {
+    let schema = interface_schema();
+    let actions = schema["actions"].as_array().unwrap();
+    assert!(actions.len() >= 6);
+    assert_eq!(actions.len(), Action::all().len());
+    let codes: std::collections::HashSet<u64> = actions
+        .iter()
+        .map(|action| action["code"].as_u64().unwrap())
+        .collect();
+    assert_eq!(codes.len(), actions.len());
+    for action in actions {
+        let code = action["code"].as_u64().unwrap() as u8;
+        let decoded = Action::from_code(code).unwrap();
+        assert_eq!(action["name"], format!("{:?}", decoded));
+    }
+    assert_eq!(schema["state"]["left"]["health"], "number");
+    assert!(schema["win_reason"]
+        .as_array()
+        .unwrap()
+        .contains(&"Surrender".into()));
+}
+
+#[test]
+fn schema_lists_every_reason_and_status() {
+    // Exhaustive, so a new variant has to be given a place here before ALL is checked again.
+    let reason_place = |reason| match reason {
+        WinReason::Lethal => 0,
+        WinReason::TurnLimit => 1,
+        WinReason::Tiebreak => 2,
+        WinReason::Surrender => 3,
+        WinReason::DamageThreshold => 4,
+    };
+    let status_place = |status| match status {
+        GameStatus::Ongoing => 0,
+        GameStatus::Winner(_) => 1,
+        GameStatus::Draw => 2,
+    };
+    assert_eq!(WinReason::ALL.map(reason_place), [0, 1, 2, 3, 4]);
+    assert_eq!(GameStatus::ALL.map(status_place), [0, 1, 2]);
+
+    let schema = interface_schema();
+    let reasons: Vec<WinReason> = serde_json::from_value(schema["win_reason"].clone()).unwrap();
+    assert_eq!(reasons, WinReason::ALL);
+    assert_eq!(
+        schema["game_status"],
+        serde_json::json!(["Ongoing", "Winner", "Draw"])
+    );
+}
+
+#[test]
+fn hints_flag_dominated_moves_only() {
+    let game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    // Release without any charges is a wasted turn.
+    let hint = game.hint(Side::Left, Action::Release).unwrap();
+    assert!(hint.contains("Release"));
+    assert!(game.hint(Side::Right, Action::Release).is_some());
+    assert_eq!(game.hint(Side::Left, Action::LightningBolt), None);
+    assert_eq!(game.hint(Side::Right, Action::LightningBolt), None);
+}
+
+#[test]
+fn cached_status_matches_a_fresh_position() {
+    let config = GameConfig {
+        max_mana: 15,
+        ..GameConfig::default()
+    };
+    let mut game = Game::with_config(config.clone()).unwrap();
+    let mut left = RandomStrategy;
+    let mut right = RandomStrategy;
+    let mut rng = StdRng::seed_from_u64(11);
+    loop {
+        // Decoding builds a new game from the wizards alone, so its status is computed afresh.
+        let fresh = Game::decode(game.encode().unwrap(), config.clone()).unwrap();
+        assert_eq!(game.status(), fresh.status());
+        assert_eq!(game.result().is_some(), game.game_completed().0);
+        if game.status() != GameStatus::Ongoing {
+            break;
+        }
+        let left_action = left.choose(&game, Side::Left, &mut rng);
+        let right_action = right.choose(&game, Side::Right, &mut rng);
+        let _ = game.tick(left_action, right_action);
+    }
+
+    let instant = Game::with_config(GameConfig {
+        max_turns: Some(0),
+        ..GameConfig::default()
+    })
+    .unwrap();
+    assert_eq!(instant.status(), GameStatus::Draw);
+}
+
+#[test]
+fn games_run_on_either_rng_source() {
+    let play = |mut left_rng: GameRng, mut right_rng: GameRng| {
+        let mut game = Game::with_config(GameConfig {
+            max_turns: Some(200),
+            ..GameConfig::default()
+        })
+        .unwrap();
+        while game.result().is_none() {
+            let _ = game.step_with(
+                &mut RandomStrategy,
+                &mut RandomStrategy,
+                &mut left_rng,
+                &mut right_rng,
+            );
+        }
+        game
+    };
+    let entropy = play(GameRng::from_entropy(), GameRng::from_entropy());
+    assert!(entropy.result().is_some());
+    let seeded = play(GameRng::seeded(4), GameRng::seeded(5));
+    assert_eq!(seeded, play(GameRng::seeded(4), GameRng::seeded(5)));
+}
+
+#[test]
+fn opening_book_covers_every_legal_first_turn() {
+    let game = Game::new();
+    let book = game.opening_book();
+    let lefts = game.available_actions(Side::Left).len();
+    let rights = game.available_actions(Side::Right).len();
+    assert_eq!(book.len(), lefts * rights);
+    let strikes = book
+        .iter()
+        .find(|opening| opening.left == Action::Strike && opening.right == Action::Strike)
+        .unwrap();
+    assert_eq!(strikes.position.left.health, 23);
+    assert_eq!(strikes.position.right.health, 23);
+    assert_eq!(strikes.score, 0);
+}
+
+fn partial_casting_game(mana: u8) -> Game {
+    Game::with_config(GameConfig {
+        starting_mana: mana,
+        partial_casting: true,
+        ..GameConfig::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn partial_casting_scales_damage_by_mana_paid() {
+    let mut full = partial_casting_game(2);
+    full.tick(Action::LightningBolt, Action::Concentrate)
+        .unwrap();
+    assert_eq!(full.wizard(Side::Right).health(), 20);
+    assert_eq!(full.wizard(Side::Left).mana(), 1);
+
+    // 1 of the 2 mana paid, 5 damage halved and rounded down.
+    let mut half = partial_casting_game(1);
+    half.tick(Action::LightningBolt, Action::Concentrate)
+        .unwrap();
+    assert_eq!(half.wizard(Side::Right).health(), 23);
+    assert_eq!(half.wizard(Side::Left).mana(), 1);
+}
+
+#[test]
+fn partial_casting_is_off_by_default() {
+    assert!(!GameConfig::default().partial_casting);
+    let mut game = Game::new();
+    assert!(matches!(
+        game.tick(Action::LightningBolt, Action::Concentrate),
+        Err(GameError::NotEnoughMana { .. })
+    ));
+}
+
+#[test]
+fn overkill_damage_counts_as_saturation() {
+    let mut game = low_health_game(1);
+    assert_eq!(game.saturation().health_floor, 0);
+    game.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert_eq!(game.saturation().health_floor, 1);
+
+    let report = simulate(&GameConfig::default(), 50, 3).unwrap();
+    assert!(report.saturation.health_floor > 0);
+}
+
+#[test]
+fn replay_frames_run_from_start_to_end() {
+    let replay = recorded_game(9, &[(Action::Strike, Action::Concentrate); 5]);
+    let frames: Vec<FrameState> = replay.frames().unwrap();
+    assert_eq!(frames.len(), replay.turns.len() + 1);
+
+    let first = &frames[0];
+    assert_eq!(first.turn, 0);
+    assert_eq!((first.left.health, first.left.max_health), (9, 9));
+    assert_eq!(first.right.mana, 1);
+    assert_eq!(first.left.last_action, None);
+
+    let last = frames.last().unwrap();
+    let end = replay.turns.last().unwrap();
+    assert_eq!(last.turn, 5);
+    assert_eq!(last.right.health, end.right.health);
+    assert_eq!(last.right.mana, end.right.mana);
+    assert_eq!(last.left.last_action, Some(Action::Strike));
+}
+
+// Plays Concentrate and remembers what foresight showed it.
+struct Seer {
+    seen: Option<Action>,
+}
+
+impl Strategy for Seer {
+    fn choose(&mut self, game: &Game, side: Side, _rng: &mut dyn rand::RngCore) -> Action {
+        self.seen = game.foresight(side);
+        Action::Concentrate
+    }
+}
+
+fn foresight_game(left: Action, right: Action) -> Game {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    game.tick(left, right).unwrap();
+    game
+}
+
+#[test]
+fn foresight_shows_the_opponents_action() {
+    let mut game = foresight_game(Action::Foresight, Action::Strike);
+    assert!(game.has_foresight(Side::Left));
+    assert!(!game.has_foresight(Side::Right));
+    let mut seer = Seer { seen: None };
+    let mut striker = WeightedRandomStrategy::new([(Action::Fireball, 1.0)].into());
+    let mut rng = StdRng::seed_from_u64(0);
+    game.step_with(&mut seer, &mut striker, &mut rng.clone(), &mut rng)
+        .unwrap();
+    assert_eq!(seer.seen, Some(Action::Fireball));
+    assert!(!game.has_foresight(Side::Left));
+}
+
+#[test]
+fn double_foresight_cancels_out() {
+    let mut game = foresight_game(Action::Foresight, Action::Foresight);
+    assert!(!game.has_foresight(Side::Left));
+    assert!(!game.has_foresight(Side::Right));
+    let (mut left, mut right) = (Seer { seen: None }, Seer { seen: None });
+    let mut rng = StdRng::seed_from_u64(0);
+    game.step_with(&mut left, &mut right, &mut rng.clone(), &mut rng)
+        .unwrap();
+    assert_eq!((left.seen, right.seen), (None, None));
+}
+
+fn bracket_field(size: usize) -> Vec<Entrant> {
+    (0..size)
+        .map(|index| {
+            Entrant::new(
+                format!("adaptive-{}", index),
+                AdaptiveStrategy::new(index as u8 * 4),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn single_elimination_of_four_takes_two_rounds() {
+    let config = GameConfig {
+        max_turns: Some(50),
+        ..GameConfig::default()
+    };
+    let mut entrants = bracket_field(4);
+    let result = bracket(&mut entrants, Elimination::Single, 3, &config, 1).unwrap();
+    assert_eq!(result.rounds(), 2);
+    assert_eq!(result.matches.len(), 3);
+    assert_eq!(
+        result
+            .matches
+            .iter()
+            .filter(|played| played.round == 1)
+            .count(),
+        2
+    );
+    let last = result.matches.last().unwrap();
+    assert_eq!(last.winner, result.champion);
+    assert!(entrants
+        .iter()
+        .any(|entrant| entrant.name == result.champion));
+}
+
+#[test]
+fn double_elimination_gives_byes_and_needs_two_losses() {
+    let config = GameConfig {
+        max_turns: Some(50),
+        ..GameConfig::default()
+    };
+    let mut entrants = bracket_field(3);
+    let result = bracket(&mut entrants, Elimination::Double, 1, &config, 2).unwrap();
+    assert!(result.matches.iter().any(|played| played.result.is_none()));
+    for entrant in &entrants {
+        let losses = result
+            .matches
+            .iter()
+            .filter(|played| {
+                played.result.as_ref().is_some_and(|record| {
+                    (record.left == entrant.name || record.right == entrant.name)
+                        && played.winner != entrant.name
+                })
+            })
+            .count();
+        if entrant.name == result.champion {
+            assert!(losses <= 1);
+        } else {
+            assert_eq!(losses, 2);
+        }
+    }
+}
+
+#[test]
+fn random_play_keeps_health_and_mana_in_bounds() {
+    let mut rng = StdRng::seed_from_u64(167);
+    let mut ticks = 0;
+    for max_mana in [3, 6, 15, u8::MAX] {
+        let config = GameConfig {
+            max_mana,
+            last_stand: true,
+            partial_casting: true,
+            max_turns: Some(200),
+            ..GameConfig::default()
+        };
+        for seed in 0..20 {
+            let mut game = Game::with_seed(config.clone(), seed).unwrap();
+            game.apply_effect(
+                Side::Left,
+                StatusEffect::new(EffectKind::Regeneration, 5, 3),
+            );
+            while game.result().is_none() {
+                let left = RandomStrategy.choose(&game, Side::Left, &mut rng);
+                let right = RandomStrategy.choose(&game, Side::Right, &mut rng);
+                if game.tick(left, right).is_err() {
+                    continue;
+                }
+                ticks += 1;
+                for side in [Side::Left, Side::Right] {
+                    assert!(game.wizard(side).health() <= config.starting_health);
+                    assert!(game.wizard(side).mana() <= max_mana);
+                }
+            }
+        }
+    }
+    assert!(ticks > 1000);
+}
+
+#[test]
+fn run_batch_returns_every_game() {
+    let config = GameConfig::default();
+    let results = run_batch(&config, &mut RandomStrategy, &mut RandomStrategy, 10, 9).unwrap();
+    assert_eq!(results.len(), 10);
+    assert!(results
+        .iter()
+        .all(|result| result.reason == WinReason::Lethal));
+
+    let report = simulate(&config, 10, 9).unwrap();
+    let left_wins = results
+        .iter()
+        .filter(|result| result.winner == Some(Side::Left))
+        .count();
+    let draws = results
+        .iter()
+        .filter(|result| result.winner.is_none())
+        .count();
+    assert_eq!(report.left_wins as usize, left_wins);
+    assert_eq!(report.draws as usize, draws);
+}
+
+// Mana `side`'s opponent spent on attacks that `side` shielded away, over a whole game.
+fn mana_wasted_into_shields(mut defender: impl Strategy, side: Side) -> u32 {
+    let config = GameConfig {
+        starting_mana: 5,
+        max_mana: 6,
+        max_turns: Some(30),
+        ..GameConfig::default()
+    };
+    let mut game = Game::with_config(config.clone()).unwrap();
+    let mut attacker = AdaptiveStrategy::new(u8::MAX);
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut wasted = 0;
+    while game.result().is_none() {
+        let own = defender.choose(&game, side, &mut rng);
+        let theirs = attacker.choose(&game, side.opponent(), &mut rng);
+        let (left, right) = match side {
+            Side::Left => (own, theirs),
+            _ => (theirs, own),
+        };
+        game.tick(left, right).unwrap();
+        if game.events().contains(&GameEvent::Blocked { side }) {
+            wasted += config.spells.mana_spent(theirs) as u32;
+        }
+    }
+    wasted
+}
+
+#[test]
+fn pressure_strategy_baits_attacks_into_shields() {
+    let greedy = mana_wasted_into_shields(AdaptiveStrategy::new(u8::MAX), Side::Left);
+    let pressure = mana_wasted_into_shields(PressureStrategy::default(), Side::Left);
+    assert!(pressure > greedy);
+    assert!(strategy_by_name("pressure").is_some());
+}
+
+#[test]
+fn pressure_strategy_shields_a_flush_opponent_under_the_default_cap() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut pressure = PressureStrategy::default();
+    // LightningBolt costs 2, so 4 mana is the margin of 2 to spare, far below max_mana.
+    let flush = position((20, 5), (20, 4));
+    assert_eq!(
+        pressure.choose(&flush, Side::Left, &mut rng),
+        Action::ManaShield
+    );
+    let short = position((20, 5), (20, 3));
+    assert_eq!(
+        pressure.choose(&short, Side::Left, &mut rng),
+        Action::LightningBolt
+    );
+}
+
+#[test]
+fn every_action_has_a_name_in_every_language() {
+    for &action in Action::all() {
+        for lang in [Lang::English, Lang::Spanish, Lang::German] {
+            assert!(!action.localized_name(lang).is_empty());
+        }
+        assert_eq!(action.to_string(), action.localized_name(Lang::English));
+    }
+    assert_eq!(
+        Action::LightningBolt.localized_name(Lang::from_code("es")),
+        "Relámpago"
+    );
+    assert_eq!(Lang::from_code("xx"), Lang::English);
+    assert_eq!(
+        Action::ManaShield.localized_name(Lang::from_code("xx")),
+        "Mana Shield"
+    );
+}
+
+#[test]
+fn defensive_stalemate_runs_to_the_turn_limit() {
+    let max_turns = 20_000;
+    let config = GameConfig {
+        max_mana: 20,
+        max_turns: Some(max_turns),
+        ..GameConfig::default()
+    };
+    let turtle = || {
+        WeightedRandomStrategy::new(
+            [
+                (Action::ManaShield, 1.0),
+                (Action::Reflect, 1.0),
+                (Action::Concentrate, 1.0),
+            ]
+            .into(),
+        )
+    };
+    let (mut left, mut right) = (turtle(), turtle());
+    let mut left_rng = StdRng::seed_from_u64(171);
+    let mut right_rng = StdRng::seed_from_u64(172);
+    let mut game = Game::with_config(config).unwrap();
+    while game.result().is_none() {
+        game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+            .unwrap();
+    }
+    let result = game.result().unwrap();
+    assert_eq!(result.turns, max_turns);
+    assert_eq!(game.turn_count(), max_turns);
+    assert_eq!(result.reason, WinReason::TurnLimit);
+    assert_eq!(result.winner, None);
+    assert!(game.saturation().mana_cap > 0);
+    assert!(game.wizard(Side::Left).mana() <= 20);
+}
+
+#[test]
+fn the_healthier_wizard_wins_the_tiebreak_at_the_turn_limit() {
+    let mut game = Game::with_config(GameConfig {
+        max_turns: Some(2),
+        ..GameConfig::default()
+    })
+    .unwrap();
+    game.tick(Action::Concentrate, Action::Strike).unwrap();
+    assert_eq!(game.result(), None);
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    let result = game.result().unwrap();
+    assert_eq!(result.reason, WinReason::Tiebreak);
+    assert_eq!(result.winner, Some(Side::Right));
+    assert_eq!(result.turns, 2);
+}
+
+fn only(action: Action) -> WeightedRandomStrategy {
+    WeightedRandomStrategy::new([(action, 1.0)].into())
+}
+
+#[test]
+fn fireballs_into_shields_waste_mana_and_strikes_dont() {
+    let config = GameConfig {
+        starting_mana: 10,
+        max_turns: Some(20),
+        ..GameConfig::default()
+    };
+    let shielded = analyze_strategy(
+        &config,
+        &mut only(Action::Fireball),
+        &mut only(Action::ManaShield),
+        3,
+        5,
+    )
+    .unwrap();
+    assert!(shielded.wasted_mana > 0);
+    assert_eq!(shielded.wasted_mana, shielded.mana_spent);
+
+    let strikes = analyze_strategy(
+        &config,
+        &mut only(Action::Strike),
+        &mut RandomStrategy,
+        3,
+        5,
+    )
+    .unwrap();
+    assert_eq!(strikes.wasted_mana, 0);
+}
+
+#[test]
+fn damage_threshold_wins_before_zero_hp() {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 3,
+        win_condition: WinCondition::DamageDealt(5),
+        ..GameConfig::default()
+    })
+    .unwrap();
+    game.tick(Action::Strike, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).damage_dealt(), 2);
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    game.tick(Action::LightningBolt, Action::Concentrate)
+        .unwrap();
+    assert_eq!(game.wizard(Side::Right).health(), 18);
+    let result = game.result().unwrap();
+    assert_eq!(result.winner, Some(Side::Left));
+    assert_eq!(result.reason, WinReason::DamageThreshold);
+}
+
+fn infuse_game() -> Game {
+    Game::with_config(GameConfig {
+        starting_mana: 4,
+        ..GameConfig::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn infuse_heals_below_the_cap() {
+    let mut game = infuse_game();
+    game.tick(Action::Concentrate, Action::LightningBolt)
+        .unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 20);
+    game.tick(Action::Infuse, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 23);
+}
+
+#[test]
+fn infuse_at_the_cap_still_costs_mana() {
+    let mut game = infuse_game();
+    game.tick(Action::Infuse, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 25);
+    assert_eq!(game.wizard(Side::Left).mana(), 1);
+}
+
+#[test]
+fn infuse_cant_be_reflected() {
+    let mut game = infuse_game();
+    game.tick(Action::Concentrate, Action::Fireball).unwrap();
+    game.tick(Action::Infuse, Action::Reflect).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 25);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+    assert!(!Action::Infuse.is_offensive());
+}
+
+// `Game::decode` packs HP in 6 bits per wizard and mana in 4.
+fn position(left: (u8, u8), right: (u8, u8)) -> Game {
+    let code =
+        left.0 as u32 | (right.0 as u32) << 6 | (left.1 as u32) << 12 | (right.1 as u32) << 16;
+    Game::decode(code, GameConfig::default()).unwrap()
+}
+
+#[test]
+fn mutual_mana_burns_use_pre_turn_mana() {
+    let mut game = position((20, 5), (20, 3));
+    game.tick(Action::ManaBurn, Action::ManaBurn).unwrap();
+    // 5 - 2 cost - 3 burned + 1 and 3 - 2 cost - 3 burned (floored at 0) + 1.
+    assert_eq!(game.wizard(Side::Left).mana(), 1);
+    assert_eq!(game.wizard(Side::Right).mana(), 1);
+}
+
+#[test]
+fn turn_resolution_doesnt_depend_on_side() {
+    for (left, right) in [((4, 5), (9, 2)), ((2, 15), (3, 6)), ((25, 0), (1, 9))] {
+        let game = position(left, right);
+        let mirrored = position(right, left);
+        for &left_action in Action::all() {
+            for &right_action in Action::all() {
+                let Ok(next) = game.simulate_tick(left_action, right_action) else {
+                    continue;
+                };
+                let flipped = mirrored.simulate_tick(right_action, left_action).unwrap();
+                for side in [Side::Left, Side::Right] {
+                    let (a, b) = (next.wizard(side), flipped.wizard(side.opponent()));
+                    assert_eq!(
+                        (a.health(), a.mana(), a.charges(), a.damage_dealt()),
+                        (b.health(), b.mana(), b.charges(), b.damage_dealt()),
+                        "{:?} vs {:?} from {:?} / {:?}",
+                        left_action,
+                        right_action,
+                        left,
+                        right
+                    );
+                }
+                assert_eq!(
+                    next.status() == GameStatus::Draw,
+                    flipped.status() == GameStatus::Draw
+                );
+            }
+        }
+    }
+}
+
+fn bolt_into_reflect(reflect_blocks: bool) -> Game {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 3,
+        reflect_blocks,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    game.tick(Action::LightningBolt, Action::Reflect).unwrap();
+    game
+}
+
+#[test]
+fn reflect_blocks_by_default() {
+    assert!(GameConfig::default().reflect_blocks);
+    let game = bolt_into_reflect(true);
+    assert_eq!(game.wizard(Side::Left).health(), 19);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+}
+
+#[test]
+fn reflect_without_blocking_takes_half() {
+    let game = bolt_into_reflect(false);
+    assert_eq!(game.wizard(Side::Left).health(), 19);
+    // Half of the 5 damage, rounded down.
+    assert_eq!(game.wizard(Side::Right).health(), 23);
+}
+
+#[test]
+fn legal_action_pairs_cover_both_sides() {
+    let game = position((20, 2), (20, 0));
+    let lefts = game.available_actions(Side::Left);
+    let rights = game.available_actions(Side::Right);
+    let pairs = game.legal_action_pairs();
+    assert_eq!(pairs.len(), lefts.len() * rights.len());
+    assert!(pairs.contains(&(Action::Fireball, Action::Strike)));
+    assert!(!pairs.iter().any(|&(_, right)| right == Action::Fireball));
+}
+
+#[test]
+fn wizards_can_start_poisoned_and_charged() {
+    let poison = StatusEffect::new(EffectKind::Poison, 3, -1);
+    let config = GameConfig {
+        left_wizard: WizardConfig {
+            starting_effects: vec![poison; 2],
+            starting_charges: 2,
+            ..WizardConfig::default()
+        },
+        ..GameConfig::default()
+    };
+    let mut game = Game::with_config(config.clone()).unwrap();
+    assert_eq!(game.wizard(Side::Left).charges(), 2);
+    assert_eq!(game.wizard(Side::Right).effects(), &[]);
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 23);
+    assert_eq!(game.wizard(Side::Right).health(), 25);
+
+    let overcharged = GameConfig {
+        left_wizard: WizardConfig {
+            starting_charges: 4,
+            ..WizardConfig::default()
+        },
+        ..GameConfig::default()
+    };
+    assert!(Game::with_config(overcharged).is_err());
+}
+
+#[test]
+fn coin_flip_breaks_the_same_tie_the_same_way() {
+    let double_knockout = |health: u8| {
+        let mut game = Game::with_config(GameConfig {
+            starting_health: health,
+            simultaneous_death: SimultaneousDeathRule::CoinFlip,
+            ..GameConfig::default()
+        })
+        .unwrap();
+        game.tick(Action::Strike, Action::Strike).unwrap();
+        game.result().unwrap()
+    };
+    let winners: Vec<Option<Side>> = (1..=2)
+        .chain(1..=2)
+        .map(|health| double_knockout(health).winner)
+        .collect();
+    assert!(winners.iter().all(|winner| winner.is_some()));
+    assert_eq!(winners[..2], winners[2..]);
+    assert_eq!(double_knockout(1).reason, WinReason::Lethal);
+}
+
+#[test]
+fn legal_move_previews_match_the_real_turn() {
+    let game = position((20, 5), (20, 4));
+    let preview = game.legal_moves_with_preview(Side::Right);
+    let actions: Vec<Action> = preview.iter().map(|&(action, _)| action).collect();
+    assert_eq!(actions, game.available_actions(Side::Right));
+    for (action, mana) in preview {
+        let mut next = game.clone();
+        next.tick(Action::Concentrate, action).unwrap();
+        assert_eq!(next.wizard(Side::Right).mana(), mana, "{:?}", action);
+    }
+    let mana_after = |action| preview_mana(&game, Side::Right, action);
+    assert_eq!(mana_after(Action::Strike), Some(5));
+    assert_eq!(mana_after(Action::LightningBolt), Some(3));
+    assert_eq!(mana_after(Action::Exchange), None);
+}
+
+fn preview_mana(game: &Game, side: Side, action: Action) -> Option<u8> {
+    game.legal_moves_with_preview(side)
+        .into_iter()
+        .find(|&(previewed, _)| previewed == action)
+        .map(|(_, mana)| mana)
+}
+
+#[test]
+fn a_strike_at_a_manaless_wizard_on_2_hp_is_a_forced_win() {
+    let game = position((20, 5), (2, 0));
+    assert!(game.has_forced_win(Side::Left, 1));
+    assert!(!game.has_forced_win(Side::Right, 1));
+    assert!(!position((20, 5), (20, 5)).has_forced_win(Side::Left, 1));
+}
+
+#[test]
+fn position_scores_weigh_health_over_mana() {
+    let game = position((20, 5), (15, 2));
+    // 5 HP ahead at 3 a point and 3 mana ahead at 1 a point.
+    assert_eq!(game.evaluate_position(Side::Left), 18);
+    assert_eq!(game.evaluate_position(Side::Right), -18);
+    assert_eq!(position((9, 4), (9, 4)).evaluate_position(Side::Left), 0);
+}
+
+#[test]
+fn adaptive_strategy_explains_its_last_choice() {
+    let mut strategy = AdaptiveStrategy::default();
+    let mut rng = StdRng::seed_from_u64(0);
+    assert_eq!(strategy.explain(), None);
+
+    let mut game = position((10, 5), (20, 5));
+    let action = strategy.choose(&game, Side::Left, &mut rng);
+    assert_eq!(
+        strategy.explain().as_deref(),
+        Some("behind by 10 HP, playing safe with Reflect")
+    );
+    game.tick(action, Action::Strike).unwrap();
+
+    // Right is still well ahead after its reflected strike, so it attacks.
+    let action = strategy.choose(&game, Side::Right, &mut rng);
+    assert_eq!(action, Action::LightningBolt);
+    assert_eq!(
+        strategy.explain().as_deref(),
+        Some("not behind by more than 5 HP, attacking with the biggest affordable spell, LightningBolt")
+    );
+}
+
+#[test]
+fn exchange_swaps_health_and_two_cancel_out() {
+    let mut game = position((10, 5), (20, 5));
+    game.tick(Action::Exchange, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 20);
+    assert_eq!(game.wizard(Side::Right).health(), 10);
+
+    let mut game = position((10, 5), (20, 5));
+    game.tick(Action::Exchange, Action::Exchange).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 10);
+    assert_eq!(game.wizard(Side::Right).health(), 20);
+}
+
+#[test]
+fn last_stand_only_surges_once() {
+    let config = GameConfig {
+        last_stand: true,
+        ..GameConfig::default()
+    };
+    let mut game = Game::decode(3 | 20 << 6 | 5 << 12 | 5 << 16, config).unwrap();
+    game.tick(Action::Concentrate, Action::Strike).unwrap();
+    // 4 from Concentrate, 1 passive and 2 from the surge.
+    assert_eq!(game.wizard(Side::Left).health(), 1);
+    assert_eq!(game.wizard(Side::Left).mana(), 12);
+    assert!(game.wizard(Side::Left).last_stand_used());
+
+    game.tick(Action::Infuse, Action::Concentrate).unwrap();
+    game.tick(Action::Concentrate, Action::Fireball).unwrap();
+    assert_eq!(game.wizard(Side::Left).health(), 1);
+    assert_eq!(game.wizard(Side::Left).mana(), 9 + 5);
+}
+
+#[test]
+fn blood_magic_trades_health_for_mana() {
+    let mut game = position((20, 1), (20, 1));
+    let mut ramp = Vec::new();
+    for _ in 0..3 {
+        game.tick(Action::BloodMagic, Action::Concentrate).unwrap();
+        let left = game.wizard(Side::Left);
+        ramp.push((left.health(), left.mana()));
+    }
+    // 3 HP for 5 mana and the passive 1, every turn.
+    assert_eq!(ramp, [(17, 7), (14, 13), (11, 19)]);
+}
+
+#[test]
+fn blood_magic_is_illegal_at_3_hp_or_less() {
+    assert!(position((4, 1), (20, 1)).can_cast(Side::Left, Action::BloodMagic));
+    let mut game = position((3, 1), (20, 1));
+    assert!(!game.can_cast(Side::Left, Action::BloodMagic));
+    assert_eq!(
+        game.tick(Action::BloodMagic, Action::Concentrate),
+        Err(GameError::NotEnoughHealth {
+            side: Side::Left,
+            action: Action::BloodMagic,
+            have: 3,
+            need: 4,
+        })
+    );
+    assert_eq!(game.turn_count(), 0);
+}
+
+#[test]
+fn a_reflected_bolt_emits_its_events_in_order() {
+    let mut game = position((20, 5), (20, 5));
+    game.tick(Action::LightningBolt, Action::Reflect).unwrap();
+    assert_eq!(
+        game.events(),
+        [
+            GameEvent::TurnStarted { turn: 1 },
+            GameEvent::ManaChanged {
+                side: Side::Left,
+                delta: -2,
+            },
+            GameEvent::ManaChanged {
+                side: Side::Right,
+                delta: -2,
+            },
+            GameEvent::Reflected {
+                side: Side::Right,
+                amount: 6,
+            },
+            GameEvent::DamageDealt {
+                side: Side::Left,
+                amount: 6,
+            },
+            GameEvent::ManaChanged {
+                side: Side::Left,
+                delta: 1,
+            },
+            GameEvent::ManaChanged {
+                side: Side::Right,
+                delta: 1,
+            },
+        ]
+    );
+}
+
+#[test]
+fn hits_that_deal_nothing_emit_no_damage() {
+    // A 1 damage Strike bounced off Reflect leaves the reflector a share of 0.
+    let mut spells = SpellTable::default();
+    spells.set(
+        Action::Strike,
+        Spell {
+            damage: 1,
+            mana_cost: 1,
+        },
+    );
+    let config = GameConfig {
+        starting_mana: 5,
+        reflect_blocks: false,
+        spells,
+        ..GameConfig::default()
+    };
+    for (left, right) in [
+        (Action::Strike, Action::Reflect),
+        (Action::Fireball, Action::ManaShield),
+        (Action::Strike, Action::Teleport),
+    ] {
+        let mut game = Game::with_config(config.clone()).unwrap();
+        game.tick(left, right).unwrap();
+        assert!(
+            !game.events().contains(&GameEvent::DamageDealt {
+                side: Side::Right,
+                amount: 0,
+            }),
+            "{:?} into {:?}",
+            left,
+            right
+        );
+    }
+}
+
+#[test]
+fn preview_all_covers_the_action_bar() {
+    let game = position((20, 2), (20, 0));
+    let preview = game.preview_all(Side::Left);
+    assert_eq!(preview.len(), Action::all().len());
+    for (entry, &action) in preview.iter().zip(Action::all()) {
+        assert_eq!(entry.action, action);
+        assert_eq!(entry.legal, game.can_cast(Side::Left, action));
+    }
+    let bolt = preview[Action::LightningBolt.code() as usize];
+    assert!(bolt.legal);
+    assert_eq!((bolt.mana_cost, bolt.best_case_damage), (2, 5));
+    assert!(!preview[Action::Exchange.code() as usize].legal);
+    assert_eq!(preview[Action::Release.code() as usize].best_case_damage, 0);
+    let json = serde_json::to_value(&preview).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), preview.len());
+}
+
+#[test]
+fn compel_forces_a_repeat_strike() {
+    let mut game = position((20, 5), (20, 5));
+    game.tick(Action::Concentrate, Action::Strike).unwrap();
+    game.tick(Action::Compel, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Right).compelled(), Some(Action::Strike));
+    let health = game.wizard(Side::Left).health();
+    game.tick(Action::Concentrate, Action::Fireball).unwrap();
+    assert_eq!(game.wizard(Side::Right).last_action(), Some(Action::Strike));
+    assert_eq!(game.wizard(Side::Left).health(), health - 2);
+    assert_eq!(game.wizard(Side::Right).compelled(), None);
+}
+
+#[test]
+fn compel_fizzles_without_a_previous_action() {
+    let mut game = position((20, 5), (20, 5));
+    game.tick(Action::Compel, Action::Concentrate).unwrap();
+    assert_eq!(game.wizard(Side::Right).compelled(), None);
+    game.tick(Action::Concentrate, Action::Strike).unwrap();
+    assert_eq!(game.wizard(Side::Right).last_action(), Some(Action::Strike));
+}
+
+#[test]
+fn who_would_win_has_one_valid_cell_per_config() {
+    let grid = ConfigGrid {
+        starting_mana: vec![0, 3],
+        mana_per_turn: vec![1, 2],
+        starting_health: vec![10, 25],
+    };
+    let cells = who_would_win(
+        &GameConfig::default(),
+        &grid,
+        &mut RandomStrategy,
+        &mut AdaptiveStrategy::default(),
+        20,
+        7,
+    )
+    .unwrap();
+    assert_eq!(cells.len(), 8);
+    for cell in &cells {
+        let rates = [cell.left_rate, cell.right_rate, cell.draw_rate];
+        assert!(rates.iter().all(|rate| (0.0..=1.0).contains(rate)));
+        assert!((rates.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+    assert_eq!(
+        (
+            cells[7].starting_mana,
+            cells[7].mana_per_turn,
+            cells[7].starting_health
+        ),
+        (3, 2, 25)
+    );
+}
+
+fn burn_two_mana(strict_arithmetic: bool) -> (Game, Result<(), GameError>) {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 2,
+        strict_arithmetic,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let result = game.tick(Action::ManaBurn, Action::Strike);
+    (game, result)
+}
+
+#[test]
+fn normal_arithmetic_saturates_an_overdrawn_burn() {
+    let (game, result) = burn_two_mana(false);
+    assert!(result.is_ok());
+    assert_eq!(game.wizard(Side::Right).mana(), 1);
+    assert_eq!(game.saturation().mana_floor, 1);
+}
+
+#[test]
+fn strict_arithmetic_rejects_an_overdrawn_burn() {
+    let (game, result) = burn_two_mana(true);
+    assert_eq!(
+        result,
+        Err(GameError::Underflow {
+            side: Side::Right,
+            field: "mana",
+            have: 2,
+            need: 3,
+        })
+    );
+    assert_eq!(game.turn_count(), 0);
+    assert_eq!(game.wizard(Side::Left).mana(), 2);
+    assert_eq!(game.saturation().mana_floor, 0);
+}
+
+#[test]
+fn strict_arithmetic_games_between_strategies_finish() {
+    let config = GameConfig {
+        starting_health: 2,
+        strict_arithmetic: true,
+        max_turns: Some(50),
+        ..GameConfig::default()
+    };
+    let record = play_game(
+        &mut AdaptiveStrategy::new(u8::MAX),
+        &mut AdaptiveStrategy::new(u8::MAX),
+        config,
+        7,
+    )
+    .unwrap();
+    assert!(record.result.turns <= 50);
+}
+
+#[test]
+fn timed_out_player_plays_the_default_action() {
+    let mut game = Game::new();
+    let mut timer = TurnTimer::new(5_000, 1_000);
+    timer.submit(Side::Left, Action::Fireball);
+    assert_eq!(timer.poll(&mut game, 3_000), Ok(None));
+    assert_eq!(game.turn_count(), 0);
+
+    let played = timer.poll(&mut game, 6_000).unwrap();
+    assert_eq!(played, Some((Action::Fireball, Action::Strike)));
+    assert_eq!(game.turn_count(), 1);
+    assert_eq!(game.wizard(Side::Left).health(), 23);
+    assert_eq!(timer.submitted(Side::Left), None);
+    assert!(!timer.timed_out(6_000));
+}
+
+#[test]
+fn find_seed_digs_up_a_game_with_a_concentrate() {
+    let concentrates = |replay: &Replay| {
+        replay.turns.iter().any(|turn| {
+            turn.left_action == Action::Concentrate || turn.right_action == Action::Concentrate
+        })
+    };
+    let seed = find_seed(concentrates, 50).expect("random games concentrate often");
+    let record = play_game(
+        &mut RandomStrategy,
+        &mut RandomStrategy,
+        GameConfig::default(),
+        seed,
+    )
+    .unwrap();
+    assert!(concentrates(&record.replay));
+    assert_eq!(find_seed(|replay| replay.turns.is_empty(), 5), None);
+}
+
+#[test]
+fn aliases_parse_to_their_actions() {
+    assert_eq!("lb".parse(), Ok(Action::LightningBolt));
+    assert_eq!(" Shield ".parse(), Ok(Action::ManaShield));
+    assert_eq!("lightningbolt".parse(), Ok(Action::LightningBolt));
+    let mut parser = ActionParser::default();
+    parser.add_alias("zap", Action::Strike).unwrap();
+    assert_eq!(parser.parse("ZAP"), Ok(Action::Strike));
+    assert_eq!(
+        ActionParser::names_only().parse("lb"),
+        Err(ParseActionError::Unknown("lb".to_string()))
+    );
+}
+
+#[test]
+fn every_shown_name_parses_back() {
+    let parser = ActionParser::names_only();
+    for &action in Action::all() {
+        assert_eq!(parser.parse(&action.to_string()), Ok(action));
+        assert_eq!(parser.parse(&format!("{:?}", action)), Ok(action));
+        for lang in [Lang::Spanish, Lang::German] {
+            let name = action.localized_name(lang).to_uppercase();
+            assert_eq!(parser.parse(&name), Ok(action), "{:?} {}", lang, name);
+        }
+    }
+    assert_eq!(" mana  BURN ".parse(), Ok(Action::ManaBurn));
+}
+
+#[test]
+fn colliding_and_unknown_aliases_are_rejected() {
+    let mut parser = ActionParser::default();
+    assert_eq!(
+        parser.add_alias("strike", Action::Fireball),
+        Err(ParseActionError::Taken {
+            alias: "strike".to_string(),
+            action: Action::Strike,
+        })
+    );
+    assert!(parser.add_alias("lb", Action::Fireball).is_err());
+    assert!(parser.add_alias("lb", Action::LightningBolt).is_ok());
+    assert_eq!(
+        "frostbolt".parse::<Action>(),
+        Err(ParseActionError::Unknown("frostbolt".to_string()))
+    );
+}
+
+#[test]
+fn summary_totals_add_up() {
+    let config = GameConfig {
+        starting_mana: 5,
+        max_turns: Some(200),
+        ..GameConfig::default()
+    };
+    let mut game = Game::with_config(config.clone()).unwrap();
+    game.tick(Action::Concentrate, Action::Fireball).unwrap();
+    game.tick(Action::Infuse, Action::Strike).unwrap();
+    assert_eq!(game.summary(), None);
+    let (mut left_rng, mut right_rng) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(2));
+    while game.result().is_none() {
+        let _ = game.step_with(
+            &mut RandomStrategy,
+            &mut AdaptiveStrategy::default(),
+            &mut left_rng,
+            &mut right_rng,
+        );
+    }
+    let summary = game.summary().unwrap();
+    assert_eq!(summary.turns, game.turn_count());
+    assert!(summary.left_stats.healing_done >= 3);
+    for (side, stats) in [
+        (Side::Left, &summary.left_stats),
+        (Side::Right, &summary.right_stats),
+    ] {
+        let health = config.starting_health as u32 + stats.healing_done - stats.damage_taken;
+        assert_eq!(health, game.wizard(side).health() as u32);
+        assert_eq!(stats.action_counts.values().sum::<u32>(), summary.turns);
+        assert!(stats.mana_wasted <= stats.mana_spent);
+    }
+    assert_eq!(summary.winner, game.result().unwrap().winner);
+}
+
+#[test]
+fn compressed_replay_round_trips_smaller_than_json() {
+    let config = GameConfig::default();
+    let mut game = Game::with_seed(config.clone(), 9).unwrap();
+    let mut replay = Replay::with_seed(config.clone(), 9);
+    let cycle = [
+        (Action::Strike, Action::ManaShield),
+        (Action::ManaShield, Action::Strike),
+        (Action::Concentrate, Action::Concentrate),
+        (Action::Fireball, Action::ManaShield),
+    ];
+    for &(left, right) in cycle.iter().cycle().take(40) {
+        game.tick(left, right).unwrap();
+        replay.record(left, right, &game);
+    }
+    let bytes = replay.compress();
+    assert_eq!(Replay::decompress(&bytes, config).unwrap(), replay);
+    assert!(bytes.len() < serde_json::to_vec(&replay).unwrap().len());
+
+    let other = GameConfig {
+        starting_health: 30,
+        ..GameConfig::default()
+    };
+    assert!(Replay::decompress(&bytes, other).is_err());
+    assert!(Replay::decompress(&bytes[..bytes.len() - 1], GameConfig::default()).is_err());
+}
+
+#[test]
+fn compressed_replay_claiming_too_many_turns_is_cut_short() {
+    let config = GameConfig::default();
+    let mut bytes = Replay::with_seed(config.clone(), 9).compress();
+    // The turn count sits right after the version, config hash and seed.
+    bytes[17..21].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = Replay::decompress(&bytes, config).unwrap_err();
+    assert_eq!(err.to_string(), "compressed replay is cut short");
+}
+
+#[test]
+fn forced_lightning_bolt_overrides_the_strategy_once() {
+    let mut game = Game::with_config(GameConfig {
+        starting_mana: 5,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let (mut left, mut right) = (only(Action::Concentrate), only(Action::Concentrate));
+    let (mut left_rng, mut right_rng) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(2));
+    game.force_next_action(Side::Left, Action::LightningBolt);
+    game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+        .unwrap();
+    assert_eq!(
+        game.history().last(),
+        Some(&(Action::LightningBolt, Action::Concentrate))
+    );
+    assert_eq!(game.wizard(Side::Right).health(), 20);
+    game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+        .unwrap();
+    assert_eq!(
+        game.wizard(Side::Left).last_action(),
+        Some(Action::Concentrate)
+    );
+}
+
+#[test]
+fn illegal_forced_action_errors_or_falls_back() {
+    let mut game = Game::new();
+    let (mut left, mut right) = (only(Action::Strike), only(Action::Strike));
+    let (mut left_rng, mut right_rng) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(2));
+    game.force_next_action(Side::Left, Action::Exchange);
+    let step = game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng);
+    assert!(matches!(step, Err(GameError::NotEnoughMana { .. })));
+    assert_eq!(game.turn_count(), 0);
+
+    game.force_next_action_or_fallback(Side::Left, Action::Exchange);
+    game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+        .unwrap();
+    assert_eq!(game.history(), &[(Action::Strike, Action::Strike)]);
+}
+
+#[test]
+fn a_rejected_forced_action_is_tried_again() {
+    let mut game = Game::new();
+    let (mut left, mut right) = (only(Action::Strike), only(Action::Strike));
+    let (mut left_rng, mut right_rng) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(2));
+    game.force_next_action(Side::Left, Action::LightningBolt);
+    let step = game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng);
+    assert!(matches!(step, Err(GameError::NotEnoughMana { .. })));
+
+    game.tick(Action::Concentrate, Action::Concentrate).unwrap();
+    game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+        .unwrap();
+    game.step_with(&mut left, &mut right, &mut left_rng, &mut right_rng)
+        .unwrap();
+    assert_eq!(
+        game.history()[1..],
+        [
+            (Action::LightningBolt, Action::Strike),
+            (Action::Strike, Action::Strike)
+        ]
+    );
+}
+
+// Spells and effects never target Side::Neither, anything that tries panics instead
+// of quietly doing nothing.
+#[test]
+#[should_panic(expected = "Side::Neither has no wizard")]
+fn effects_on_neither_side_panic() {
+    let mut game = Game::new();
+    game.apply_effect(
+        Side::Neither,
+        StatusEffect {
+            kind: EffectKind::Poison,
+            turns: Some(1),
+            health_per_turn: -1,
+        },
+    );
+}
+
+#[test]
+fn win_probability_tracks_the_position() {
+    let winning = position((20, 5), (1, 0));
+    assert!(winning.win_probability(Side::Left) > 0.8);
+    let mirror = position((20, 5), (20, 5));
+    let left = mirror.win_probability_with(Side::Left, 400);
+    assert!((left - 0.5).abs() < 0.1, "{}", left);
+    assert_eq!(left + mirror.win_probability_with(Side::Right, 400), 1.0);
+}
+
+#[test]
+fn games_can_be_shared_across_threads() {
+    fn shareable<T: Send + Sync>() {}
+    shareable::<Game>();
+    let game = position((20, 5), (20, 5));
+    let odds = std::thread::scope(|scope| {
+        scope
+            .spawn(|| game.win_probability_with(Side::Left, 50))
+            .join()
+            .unwrap()
+    });
+    assert_eq!(odds, game.win_probability_with(Side::Left, 50));
+}
+
+// Right's HP after Left plays `actions` from 20 HP and 5 mana each, Right concentrating.
+fn right_health_after(actions: &[Action]) -> u8 {
+    let mut game = position((20, 5), (20, 5));
+    for &action in actions {
+        game.tick(action, Action::Concentrate).unwrap();
+    }
+    game.wizard(Side::Right).health()
+}
+
+#[test]
+fn empowered_fireball_hits_harder() {
+    assert_eq!(right_health_after(&[Action::Fireball]), 17);
+    assert_eq!(right_health_after(&[Action::Empower, Action::Fireball]), 14);
+}
+
+#[test]
+fn concentrating_after_empower_wastes_the_buff() {
+    let wasted = [Action::Empower, Action::Concentrate, Action::Fireball];
+    assert_eq!(right_health_after(&wasted), 17);
+}
+
+#[test]
+fn empower_only_boosts_one_attack() {
+    let strikes = [Action::Empower, Action::Strike, Action::Strike];
+    assert_eq!(right_health_after(&strikes), 20 - 5 - 2);
+}
+
+#[test]
+fn env_reset_observes_full_health() {
+    let mut env = GameEnv::default();
+    let observation = env.reset();
+    let game = Game::decode(observation, env.game().config().clone()).unwrap();
+    for side in [Side::Left, Side::Right] {
+        assert_eq!(game.wizard(side).health(), 25);
+    }
+    assert!(GameEnv::new(GameConfig::default()).is_err());
+}
+
+#[test]
+fn lethal_env_step_ends_the_episode() {
+    let mut env = GameEnv::new(GameConfig {
+        starting_health: 5,
+        starting_mana: 2,
+        max_mana: 15,
+        ..GameConfig::default()
+    })
+    .unwrap();
+    env.reset();
+    let (observation, left, right, done) = env
+        .step(Action::LightningBolt, Action::Concentrate)
+        .unwrap();
+    assert!(done);
+    assert_eq!((left, right), (2.0, -2.0));
+    assert_eq!(observation >> 6 & 63, 0);
+}
@@ -0,0 +1,43 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+use wizardfight_wasm::rng::GameRng;
+use wizardfight_wasm::strategy::RandomStrategy;
+use wizardfight_wasm::wasm::WasmGame;
+use wizardfight_wasm::{Action, ActionPreview, Game, GameConfig, Side};
+
+#[wasm_bindgen_test]
+fn entropy_seeded_games_finish() {
+    let mut game = Game::with_config(GameConfig {
+        max_turns: Some(100),
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let (mut left_rng, mut right_rng) = (GameRng::from_entropy(), GameRng::from_entropy());
+    while game.result().is_none() {
+        // Illegal random picks are asked again.
+        let _ = game.step_with(
+            &mut RandomStrategy,
+            &mut RandomStrategy,
+            &mut left_rng,
+            &mut right_rng,
+        );
+    }
+    assert!(game.turn_count() <= 100);
+}
+
+#[wasm_bindgen_test]
+fn previews_reach_js_as_plain_values() {
+    let mut wasm_game = WasmGame::new();
+    wasm_game
+        .tick(Action::Concentrate.code(), Action::Strike.code())
+        .unwrap();
+    let preview = wasm_game.preview_all(JsValue::from_str("Left")).unwrap();
+    let preview: Vec<ActionPreview> = serde_wasm_bindgen::from_value(preview).unwrap();
+
+    let mut game = Game::new();
+    game.tick(Action::Concentrate, Action::Strike).unwrap();
+    assert_eq!(preview, game.preview_all(Side::Left));
+    assert!(wasm_game.preview_all(JsValue::from_str("Up")).is_err());
+}